@@ -1,9 +1,13 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use env_logger::{Builder, Target, WriteStyle};
 use log::LevelFilter;
-use nanobasic;
-use nanobasic::parser::parse_file;
+use nanobasic::interpreter::{Interpreter, ProgramState};
+use nanobasic::parser::tokenizer::KeywordMode;
+use nanobasic::parser::validation::LineOrder;
+use nanobasic::parser::{ParseOptions, parse_file, parse_file_with};
 use std::fs::File;
+use std::io;
+use std::io::{BufRead, Write};
 use std::path::Path;
 
 fn tokenize_and_parse(file: impl AsRef<Path>) -> Result<()> {
@@ -15,8 +19,50 @@ fn tokenize_and_parse(file: impl AsRef<Path>) -> Result<()> {
     Ok(())
 }
 
+/// Runs a program interactively, INPUT reads from stdin. After a STOP the
+/// program is resumed by entering CONT.
+fn run_file(file: impl AsRef<Path>, options: &ParseOptions) -> Result<()> {
+    let lines = parse_file_with(file, options)?;
+    let mut interpreter = Interpreter::from_ast(lines);
+    let mut input = io::stdin().lock();
+    let mut output = io::stdout();
+
+    loop {
+        interpreter.run(&mut input, &mut output)?;
+        let ProgramState::Stopped { line_id } = interpreter.state() else {
+            return Ok(());
+        };
+
+        write!(output, "BREAK IN {line_id}, enter CONT to continue: ")?;
+        output.flush()?;
+        let mut command = String::new();
+        input.read_line(&mut command)?;
+        if !command.trim().eq_ignore_ascii_case("cont") {
+            return Ok(());
+        }
+        interpreter.cont()?;
+    }
+}
+
+/// Usage: app [--crunched] [--reject-unsorted] [FILE]
+///
+/// `--crunched` reads classic listings, where keywords are not separated
+/// from names like in `FORI=1TO9`. `--reject-unsorted` reports lines which
+/// are not in ascending order instead of sorting them.
 fn run_app() -> Result<()> {
-    tokenize_and_parse("nanobasic/Examples/factorial.bas")
+    let mut options = ParseOptions::default();
+    let mut args = std::env::args().skip(1).peekable();
+    while let Some(flag) = args.next_if(|arg| arg.starts_with("--")) {
+        match flag.as_str() {
+            "--crunched" => options.keyword_mode = KeywordMode::Crunched,
+            "--reject-unsorted" => options.line_order = LineOrder::Reject,
+            _ => bail!("Unknown option {flag}"),
+        }
+    }
+    match args.next() {
+        Some(file) => run_file(file, &options),
+        None => tokenize_and_parse("nanobasic/Examples/factorial.bas"),
+    }
 }
 
 fn main() -> Result<()> {
@@ -0,0 +1,23 @@
+REM Sort a table of numbers with bubble sort
+10 DIM A(5)
+20 LET A(0) = 42
+21 LET A(1) = 7
+22 LET A(2) = 19
+23 LET A(3) = 3
+24 LET A(4) = 25
+25 LET A(5) = 11
+30 FOR I = 0 TO 4
+40 FOR J = 0 TO 4 - I
+50 IF A(J) > A(J + 1) THEN GOSUB 100
+60 NEXT J
+70 NEXT I
+80 FOR I = 0 TO 5
+85 PRINT A(I)
+90 NEXT I
+95 GOTO 200
+REM Swap A(J) and A(J+1)
+100 LET T = A(J)
+110 LET A(J) = A(J + 1)
+120 LET A(J + 1) = T
+130 RETURN
+200 PRINT "sorted"
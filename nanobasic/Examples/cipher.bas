@@ -0,0 +1,11 @@
+REM A Caesar cipher built from the string functions
+10 LET T$ = "HELLO WORLD"
+20 LET K = 3
+25 LET R$ = LEFT$(T$, 0)
+30 FOR I = 1 TO LEN(T$)
+40 LET C = ASC(MID$(T$, I, 1))
+50 IF C >= 65 AND C <= 90 THEN LET C = MOD(C - 65 + K, 26) + 65
+60 LET R$ = R$ + CHR$(C)
+70 NEXT I
+80 PRINT R$
+90 PRINT LEFT$(R$, 5), RIGHT$(R$, 5), INSTR(R$, "RUO")
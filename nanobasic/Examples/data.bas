@@ -0,0 +1,12 @@
+REM Reads a lookup table of the days per month from DATA
+10 DIM D(12)
+20 FOR M = 1 TO 12
+30 READ D(M)
+40 NEXT M
+50 DATA 31, 28, 31, 30, 31, 30
+60 DATA 31, 31, 30, 31, 30, 31
+70 LET T = 0
+80 FOR M = 1 TO 12 : LET T = T + D(M) : NEXT M
+90 PRINT T
+100 RESTORE 60 : READ J
+110 PRINT J
@@ -0,0 +1,14 @@
+REM Grades with a block IF and a single line ELSE
+20 FOR S = 35 TO 95 STEP 20
+30 IF S >= 90 THEN
+40   LET G$ = "A"
+50 ELSEIF S >= 70 THEN
+60   LET G$ = "B"
+70 ELSEIF S >= 50 THEN
+80   LET G$ = "C"
+90 ELSE
+100   LET G$ = "F"
+110 END IF
+120 IF S >= 50 THEN LET P = 1 ELSE LET P = 0
+130 PRINT S, G$, P
+140 NEXT S
@@ -0,0 +1,8 @@
+REM Recovers from a division by zero with ON ERROR GOTO
+10 ON ERROR GOTO 100
+20 FOR I = -1 TO 1
+30 PRINT 10 / I
+40 NEXT I
+50 END
+100 PRINT "Error in line", ERL
+110 RESUME NEXT
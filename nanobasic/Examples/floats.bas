@@ -0,0 +1,8 @@
+REM Average of three measurements and a falling stone
+10 LET A = 1.5
+20 LET B = 2.25
+30 LET C = 4
+40 PRINT "Average", (A + B + C) / 3
+50 FOR T = 0 TO 1 STEP 0.25
+60 PRINT T, 0.5 * 9.81 * T * T
+70 NEXT T
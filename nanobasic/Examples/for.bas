@@ -0,0 +1,6 @@
+REM Counting loops with FOR/NEXT
+10 FOR I = 1 TO 3
+20 FOR J = 10 TO 0 STEP -5
+30 PRINT I, J
+40 NEXT J
+50 NEXT I
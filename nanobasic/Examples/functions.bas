@@ -0,0 +1,9 @@
+REM Built-in and user-defined functions, RANDOMIZE makes the dice rolls repeatable
+10 PRINT ABS(-7), SGN(-7), INT(7.9)
+20 PRINT SQR(2), MOD(17, 5)
+30 PRINT MIN(3, 1, 2), MAX(3, 1, 2)
+40 RANDOMIZE 2024
+45 DEF FNDICE(N) = INT(RND * N) + 1
+50 FOR I = 1 TO 5
+60 PRINT FNDICE(6)
+70 NEXT I
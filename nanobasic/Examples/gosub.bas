@@ -1,7 +1,7 @@
-10 GOTO 50
-20 LET A = 10
-40 RETURN
-50 LET A = 5
-60 GOSUB 20
+10 LET A = 5
+20 GOSUB 100
 REM RETURN returns to here; we expect A to be 10
-70 PRINT A
+30 PRINT A
+40 END
+100 LET A = 10
+110 RETURN
@@ -0,0 +1,9 @@
+REM Menu dispatch with ON GOSUB, choices out of range fall through
+10 FOR I = 0 TO 3
+20 ON I GOSUB 100, 200
+30 NEXT I
+40 END
+100 PRINT "one"
+110 RETURN
+200 PRINT "two"
+210 RETURN
@@ -0,0 +1,5 @@
+REM Build a message from string variables
+10 LET N$ = "World"
+20 LET M$ = "Hello " + N$
+30 PRINT M$
+40 IF N$ < "Zebra" THEN PRINT N$
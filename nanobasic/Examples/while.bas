@@ -0,0 +1,17 @@
+REM Sum of the numbers 1 to 10 with WHILE/WEND, powers of two with DO/LOOP
+10 LET I = 1
+20 LET S = 0
+30 WHILE I <= 10
+40   LET S = S + I
+50   LET I = I + 1
+60 WEND
+70 PRINT S
+80 LET P = 1
+90 DO
+100   LET P = P * 2
+110 LOOP UNTIL P > 1000
+120 PRINT P
+130 DO WHILE P > 1
+140   LET P = P / 4
+150 LOOP
+160 PRINT P
@@ -0,0 +1,48 @@
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use nanobasic::interpreter::Interpreter;
+use std::hint::black_box;
+use std::io;
+
+const ITERATIONS: usize = 200;
+
+/// Program with `size` lines that calls a subroutine near the end and jumps
+/// back to the start of its loop in every iteration
+fn jump_heavy_program(size: usize) -> String {
+    let last_line = (size + 10) * 10;
+    let mut program = vec![
+        "10 LET I = 0".to_string(),
+        "20 LET I = I + 1".to_string(),
+        format!("30 GOSUB {}", last_line - 10),
+        format!("40 IF I < {ITERATIONS} THEN GOTO 20"),
+        format!("50 GOTO {last_line}"),
+    ];
+    for line in 6..size {
+        program.push(format!("{} LET X = {line}", line * 10));
+    }
+    program.push(format!("{} RETURN", last_line - 10));
+    program.push(format!("{last_line} PRINT I"));
+    program.join("\n")
+}
+
+fn bench_jumps(c: &mut Criterion) {
+    let mut group = c.benchmark_group("goto_gosub");
+    for size in [100, 1_000, 10_000] {
+        let program = jump_heavy_program(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &program, |b, program| {
+            // Parsing is done in the setup, only the execution is measured
+            b.iter_batched(
+                || Interpreter::from_str(program).unwrap(),
+                |mut interpreter| {
+                    interpreter.run(&mut io::empty(), &mut io::sink()).unwrap();
+                    // Returned so dropping the program isn't measured
+                    black_box(interpreter)
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_jumps);
+criterion_main!(benches);
@@ -0,0 +1,43 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use nanobasic::parser::tokenizer::{tokenize, tokenize_with_regex};
+use std::fs;
+use std::hint::black_box;
+
+/// Program with `size` lines, made of the example programs repeated
+fn example_lines(size: usize) -> Vec<String> {
+    let mut paths: Vec<_> = fs::read_dir("Examples")
+        .expect("Examples directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "bas"))
+        .collect();
+    paths.sort();
+
+    let examples: Vec<String> = paths
+        .iter()
+        .flat_map(|path| {
+            let text = fs::read_to_string(path).unwrap();
+            text.lines().map(str::to_string).collect::<Vec<_>>()
+        })
+        .collect();
+    examples.into_iter().cycle().take(size).collect()
+}
+
+fn bench_tokenizer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tokenize");
+    for size in [100, 1_000, 10_000] {
+        let lines = example_lines(size);
+        let bytes = lines.iter().map(|line| line.len() as u64 + 1).sum();
+        group.throughput(Throughput::Bytes(bytes));
+
+        group.bench_with_input(BenchmarkId::new("scanner", size), &lines, |b, lines| {
+            b.iter(|| tokenize(black_box(lines)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("regex", size), &lines, |b, lines| {
+            b.iter(|| tokenize_with_regex(black_box(lines)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_tokenizer);
+criterion_main!(benches);
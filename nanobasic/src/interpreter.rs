@@ -1,18 +1,46 @@
+mod builtins;
+mod data;
+mod error_handling;
+mod functions;
+pub mod random;
+pub mod value;
+
 use super::parser::ParseError;
-use crate::parser::statements::if_statement::{BooleanExpression, IfStatement, RelationalOperator};
+use crate::parser::builtins::is_user_function;
+use crate::parser::statements::def_statement::FunctionDefinition;
+use crate::parser::statements::dim_statement::ArrayDeclaration;
+use crate::parser::statements::for_statement::ForStatement;
+use crate::parser::statements::if_statement::{
+    BooleanExpression, Comparison, ElseIfStatement, IfStatement, LogicalOperator,
+    RelationalOperator,
+};
+use crate::parser::statements::input_statement::InputStatement;
+use crate::parser::statements::let_statment::LetTarget;
+use crate::parser::statements::loop_statement::LoopCondition;
+use crate::parser::statements::on_statement::{JumpKind, OnStatement};
 use crate::parser::statements::print_statment::Printable;
-use crate::parser::tokenizer::tokenize;
+use crate::parser::tokenizer::{Position, tokenize_with};
 use crate::parser::{
     Line,
-    expressions::{BinaryOperator, Expression, UnaryOperator},
+    expressions::{BinaryOperation, BinaryOperator, Expression, UnaryOperator},
     statements::{Statement, let_statment::LetStatement},
 };
-use crate::parser::{Node, parse_tokens};
+use crate::parser::{Node, ParseOptions, StatementIndex, parse_tokens_with};
+use data::{DataItem, collect_data};
+use error_handling::ErrorHandling;
+use random::Random;
+use serde_json;
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::io::Write;
 use std::io::{self};
+use std::io::{BufRead, Write};
+use std::rc::Rc;
 use thiserror::Error;
-use serde_json;
+use value::Value;
+
+/// Maximum number of elements of an array, DIM of a larger array is an error
+/// instead of exhausting the memory of the host
+const MAX_ARRAY_SIZE: usize = 1 << 22;
 
 #[derive(Error, Debug)]
 pub enum InterpreterError {
@@ -22,9 +50,91 @@ pub enum InterpreterError {
     #[error("Cannot GOTO line: '{0}")]
     InvalidGoto(isize),
 
+    #[error("Cannot RESTORE line {0}")]
+    InvalidRestore(isize),
+
+    #[error("Out of DATA in line {line_id}")]
+    OutOfData { line_id: usize },
+
+    #[error("Array '{name}' used without DIM at {position}")]
+    UndimensionedArray { name: String, position: Position },
+
+    #[error(
+        "Array '{name}' is too large, at most {MAX_ARRAY_SIZE} elements are allowed at {position}"
+    )]
+    ArrayTooLarge { name: String, position: Position },
+
+    #[error("Array '{name}' is already dimensioned at {position}")]
+    RedimensionedArray { name: String, position: Position },
+
+    #[error("Index {index} of array '{name}' is out of range at {position}")]
+    IndexOutOfRange {
+        name: String,
+        index: isize,
+        position: Position,
+    },
+
+    #[error(
+        "Array '{name}' has {expected} dimension(s), but {actual} indices are given at {position}"
+    )]
+    WrongNumberOfIndices {
+        name: String,
+        expected: usize,
+        actual: usize,
+        position: Position,
+    },
+
+    #[error("Type mismatch, expected {expected} but found {actual} at {position}")]
+    TypeMismatch {
+        expected: &'static str,
+        actual: &'static str,
+        position: Position,
+    },
+
+    #[error("Division by zero in line {line_id} at {position}")]
+    DivisionByZero { line_id: usize, position: Position },
+
+    #[error("Arithmetic overflow in line {line_id} at {position}")]
+    Overflow { line_id: usize, position: Position },
+
+    #[error("Unknown function '{name}' at {position}")]
+    UnknownFunction { name: String, position: Position },
+
+    #[error(
+        "Function '{name}' expects {expected} argument(s), but {actual} are given at {position}"
+    )]
+    WrongNumberOfArguments {
+        name: String,
+        expected: usize,
+        actual: usize,
+        position: Position,
+    },
+
+    #[error("Too many nested calls of function '{name}' at {position}")]
+    CallTooDeep { name: String, position: Position },
+
+    #[error("Invalid argument for {function}, {reason} at {position}")]
+    InvalidArgument {
+        function: &'static str,
+        reason: &'static str,
+        position: Position,
+    },
+
+    #[error("RESUME without an error")]
+    ResumeWithoutError,
+
     #[error("RETURN without GOSUB")]
     ReturnWithoutGosub,
 
+    #[error("NEXT without FOR")]
+    NextWithoutFor,
+
+    #[error("NEXT '{actual}' does not match FOR '{expected}'")]
+    MismatchedNext { expected: String, actual: String },
+
+    #[error("INPUT expects more data, but the input has ended")]
+    EndOfInput,
+
     #[error("Write to output failed")]
     OutputError(#[from] io::Error),
 
@@ -34,122 +144,529 @@ pub enum InterpreterError {
     #[error["Program is already finished"]]
     Finished,
 
+    #[error["Program is stopped in line {line_id}, it has to be continued first"]]
+    Stopped { line_id: usize },
+
+    #[error["Can't continue, the program is not stopped"]]
+    CannotContinue,
+
     #[error["Failed to export Abstact Syntax Tree"]]
     ExportError(#[from] serde_json::Error),
 }
 
 pub type Result<T> = std::result::Result<T, InterpreterError>;
 
+/// Whether a program continues and why it does not
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProgramState {
+    /// The next statement can be executed
+    Running,
+    /// The program reached END or ran off its last line
+    Finished,
+    /// The program was halted by STOP, `Interpreter::cont` resumes it
+    Stopped { line_id: usize },
+    /// The program was aborted by a runtime error
+    Errored { line_id: usize },
+}
+
+/// State of an active FOR loop
+struct ForFrame {
+    variable: String,
+    end: Value,
+    step: Value,
+    /// First statement of the loop body
+    body_index: StatementIndex,
+}
+
+/// Storage of a DIM'ed array, elements are stored in row-major order
+struct Array {
+    /// Number of elements of every dimension
+    dimensions: Vec<usize>,
+    values: Vec<Value>,
+}
+
 pub struct Interpreter {
     program: Vec<Line>,
-    variables: HashMap<String, isize>,
-    statement_index: usize,
-    subroutine_stack: Vec<usize>,
+    /// Maps line numbers to their index in `program`, used for jumps
+    line_index: HashMap<usize, usize>,
+    variables: HashMap<String, Value>,
+    arrays: HashMap<String, Array>,
+    /// Program counter, the statement to be executed next
+    statement_index: StatementIndex,
+    /// Statements following the active GOSUBs
+    subroutine_stack: Vec<StatementIndex>,
+    loop_stack: Vec<ForFrame>,
     current_line: usize,
+    state: ProgramState,
+    /// Generator of RND, seeded by RANDOMIZE
+    random: Random,
+    /// Functions defined by DEF, by their upper case names
+    functions: HashMap<String, Rc<FunctionDefinition>>,
+    /// Number of active calls of user-defined functions
+    call_depth: usize,
+    /// Values of all DATA statements
+    data: Vec<DataItem>,
+    /// Index of the value in `data` which is read next
+    data_pointer: usize,
+    error_handling: ErrorHandling,
 }
 
 impl Interpreter {
     /// Create interpreter form AST = "Abstact Syntax Tree"
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(program: impl AsRef<str>) -> Result<Self> {
+        Self::from_str_with(program, &ParseOptions::default())
+    }
+
+    /// Same as `from_str`, with the given options for reading the program
+    pub fn from_str_with(program: impl AsRef<str>, options: &ParseOptions) -> Result<Self> {
         let lines = program
             .as_ref()
             .lines()
             .map(|x| x.to_string())
             .collect::<Vec<String>>();
 
-        let tokens = tokenize(&lines)?;
-        let ast = parse_tokens(&tokens)?;
+        let tokens = tokenize_with(&lines, options.keyword_mode)?;
+        let ast = parse_tokens_with(&tokens, options)?;
 
-        let self_ = Interpreter {
-            program: ast,
-            variables: HashMap::new(),
-            statement_index: 0,
-            subroutine_stack: Vec::new(),
-            current_line: 0,
-        };
-        Ok(self_)
+        Ok(Self::from_ast(ast))
     }
 
     pub fn from_ast(program: Vec<Line>) -> Self {
+        let mut line_index = HashMap::with_capacity(program.len());
+        for (index, line) in program.iter().enumerate() {
+            // The first line wins, like a linear search would
+            line_index.entry(line.line_id).or_insert(index);
+        }
+
+        let data = collect_data(&program);
+        let state = if program.is_empty() {
+            ProgramState::Finished
+        } else {
+            ProgramState::Running
+        };
+
         Interpreter {
             program,
+            line_index,
             variables: HashMap::new(),
-            statement_index: 0,
+            arrays: HashMap::new(),
+            statement_index: StatementIndex::default(),
             subroutine_stack: Vec::new(),
+            loop_stack: Vec::new(),
             current_line: 0,
+            state,
+            random: Random::default(),
+            functions: HashMap::new(),
+            call_depth: 0,
+            data,
+            data_pointer: 0,
+            error_handling: ErrorHandling::default(),
         }
     }
 
-    fn calculate_boolean_expression(&self, expression: &BooleanExpression) -> Result<bool> {
-        let left = self.calculate_expression(&expression.left_expr.content)?;
-        let right = self.calculate_expression(&expression.right_expr.content)?;
+    /// Evaluates a condition, AND and OR skip their right side if the left
+    /// side already decides the result
+    fn calculate_boolean_expression(&mut self, expression: &BooleanExpression) -> Result<bool> {
+        match expression {
+            BooleanExpression::Comparison(comparison) => self.calculate_comparison(comparison),
+            BooleanExpression::LogicalOperation(operation) => {
+                let left = self.calculate_boolean_expression(&operation.left.content)?;
+                match (operation.operator, left) {
+                    (LogicalOperator::And, false) => Ok(false),
+                    (LogicalOperator::Or, true) => Ok(true),
+                    _ => self.calculate_boolean_expression(&operation.right.content),
+                }
+            }
+            BooleanExpression::Not(operand) => {
+                Ok(!self.calculate_boolean_expression(&operand.content)?)
+            }
+        }
+    }
+
+    fn calculate_comparison(&mut self, expression: &Comparison) -> Result<bool> {
+        let left = self.calculate_expression(&expression.left_expr)?;
+        let right = self.calculate_expression(&expression.right_expr)?;
+
+        let Some(ordering) = left.compare(&right) else {
+            return Err(type_mismatch(
+                left.type_name(),
+                &right,
+                expression.right_expr.position,
+            ));
+        };
 
         use RelationalOperator::*;
 
         let result = match expression.operator {
-            Equal => left == right,
-            Greater => left > right,
-            GreaterEqual => left >= right,
-            Less => left < right,
-            LessEqual => left <= right,
-            NotEqual => left != right,
+            Equal => ordering == Ordering::Equal,
+            Greater => ordering == Ordering::Greater,
+            GreaterEqual => ordering != Ordering::Less,
+            Less => ordering == Ordering::Less,
+            LessEqual => ordering != Ordering::Greater,
+            NotEqual => ordering != Ordering::Equal,
         };
         Ok(result)
     }
 
+    /// Position of an array element in `Array::values`
+    fn array_offset(
+        &mut self,
+        name: &str,
+        indices: &[Node<Expression>],
+        position: Position,
+    ) -> Result<usize> {
+        let array = self
+            .arrays
+            .get(name)
+            .ok_or_else(|| InterpreterError::UndimensionedArray {
+                name: name.to_string(),
+                position,
+            })?;
+
+        if array.dimensions.len() != indices.len() {
+            return Err(InterpreterError::WrongNumberOfIndices {
+                name: name.to_string(),
+                expected: array.dimensions.len(),
+                actual: indices.len(),
+                position,
+            });
+        }
+
+        let mut offset = 0;
+        for (dimension, index_node) in indices.iter().enumerate() {
+            // An index may call a function, so the array is borrowed again afterwards
+            let index = self.calculate_integer(index_node)?;
+            let size = self.arrays[name].dimensions[dimension];
+            if index < 0 || index as usize >= size {
+                return Err(InterpreterError::IndexOutOfRange {
+                    name: name.to_string(),
+                    index,
+                    position: index_node.position,
+                });
+            }
+            offset = offset * size + index as usize;
+        }
+        Ok(offset)
+    }
+
+    fn dimension_array(&mut self, declaration: &Node<ArrayDeclaration>) -> Result<()> {
+        let ArrayDeclaration { name, dimensions } = &declaration.content;
+        if self.arrays.contains_key(name) {
+            return Err(InterpreterError::RedimensionedArray {
+                name: name.clone(),
+                position: declaration.position,
+            });
+        }
 
-    fn calculate_expression(&self, expression: &Expression) -> Result<isize> {
+        let mut sizes = Vec::new();
+        for dimension in dimensions {
+            let max_index = self.calculate_integer(dimension)?;
+            if max_index < 0 {
+                return Err(InterpreterError::IndexOutOfRange {
+                    name: name.clone(),
+                    index: max_index,
+                    position: dimension.position,
+                });
+            }
+            // Like in classic BASIC, DIM A(10) allows the indices 0..=10
+            sizes.push(max_index as usize + 1);
+        }
+
+        let size = sizes
+            .iter()
+            .try_fold(1usize, |size, dimension| size.checked_mul(*dimension))
+            .filter(|size| *size <= MAX_ARRAY_SIZE)
+            .ok_or_else(|| InterpreterError::ArrayTooLarge {
+                name: name.clone(),
+                position: declaration.position,
+            })?;
+        let array = Array {
+            values: vec![Value::default_for(name); size],
+            dimensions: sizes,
+        };
+        self.arrays.insert(name.clone(), array);
+        Ok(())
+    }
+
+    /// Stores a value, `position` is the origin of the value for error messages
+    fn assign(&mut self, target: &Node<LetTarget>, value: Value, position: Position) -> Result<()> {
+        let LetTarget { name, indices } = &target.content;
+        if !value.fits_name(name) {
+            let expected = Value::default_for(name).type_name();
+            return Err(type_mismatch(expected, &value, position));
+        }
+
+        if indices.is_empty() {
+            self.variables.insert(name.clone(), value);
+        } else {
+            let offset = self.array_offset(name, indices, target.position)?;
+            let array = self.arrays.get_mut(name).expect("Array was checked");
+            array.values[offset] = value;
+        }
+        Ok(())
+    }
+
+    fn overflow(&self, position: Position) -> InterpreterError {
+        InterpreterError::Overflow {
+            line_id: self.current_line,
+            position,
+        }
+    }
+
+    fn calculate_binary_operation(
+        &mut self,
+        binary_op: &BinaryOperation,
+        position: Position,
+    ) -> Result<Value> {
+        let left = self.calculate_expression(&binary_op.left)?;
+        let right = self.calculate_expression(&binary_op.right)?;
+
+        let value = match (left, right, &binary_op.operator) {
+            (
+                left @ (Value::Integer(_) | Value::Float(_)),
+                right @ (Value::Integer(_) | Value::Float(_)),
+                operator,
+            ) => self.arithmetic(*operator, left, right, position)?,
+            (Value::String(left), Value::String(right), BinaryOperator::Plus) => {
+                Value::String(left + &right)
+            }
+            (Value::String(_), right, BinaryOperator::Plus) => {
+                return Err(type_mismatch("string", &right, binary_op.right.position));
+            }
+            (left @ Value::String(_), _, _) => {
+                return Err(type_mismatch("number", &left, binary_op.left.position));
+            }
+            (_, right, _) => {
+                return Err(type_mismatch("number", &right, binary_op.right.position));
+            }
+        };
+        Ok(value)
+    }
+
+    /// Applies an operator to two numbers, checks the operands the operator
+    /// is not defined for and overflows
+    fn arithmetic(
+        &self,
+        operator: BinaryOperator,
+        left: Value,
+        right: Value,
+        position: Position,
+    ) -> Result<Value> {
+        let division_by_zero = match operator {
+            BinaryOperator::Devide | BinaryOperator::Modulo => right.as_f64() == Some(0.0),
+            BinaryOperator::IntegerDivide => truncate(&right) == Some(0),
+            BinaryOperator::Power => {
+                left.as_f64() == Some(0.0) && right.as_f64().is_some_and(|f| f < 0.0)
+            }
+            _ => false,
+        };
+        if division_by_zero {
+            return Err(InterpreterError::DivisionByZero {
+                line_id: self.current_line,
+                position,
+            });
+        }
+
+        if operator == BinaryOperator::Power
+            && left.as_f64().is_some_and(|f| f < 0.0)
+            && right.as_f64().is_some_and(|f| f.fract() != 0.0)
+        {
+            return Err(InterpreterError::InvalidArgument {
+                function: "^",
+                reason: "negative base with a fractional exponent",
+                position,
+            });
+        }
+
+        numeric_operation(operator, left, right).ok_or_else(|| self.overflow(position))
+    }
+
+    fn calculate_expression(&mut self, node: &Node<Expression>) -> Result<Value> {
         use Expression::*;
-        let value = match expression {
+        let value = match &node.content {
             BinaryOperation(binary_op) => {
-                let left = self.calculate_expression(&binary_op.left.content)?;
-                let right = self.calculate_expression(&binary_op.right.content)?;
-                match binary_op.operator {
-                    BinaryOperator::Devide => left / right,
-                    BinaryOperator::Multiply => left * right,
-                    BinaryOperator::Plus => left + right,
-                    BinaryOperator::Minus => left - right,
-                }
+                self.calculate_binary_operation(binary_op, node.position)?
             }
             UnaryOperation {
                 expression,
                 operator,
             } => match operator {
-                UnaryOperator::Minus => (-1) * self.calculate_expression(&expression.content)?,
+                UnaryOperator::Minus => match self.calculate_number(expression)? {
+                    Value::Float(f) => Value::Float(-f),
+                    Value::Integer(n) => Value::Integer(
+                        n.checked_neg()
+                            .ok_or_else(|| self.overflow(node.position))?,
+                    ),
+                    Value::String(_) => unreachable!("Checked by calculate_number"),
+                },
             },
-            NumberLiteral(n) => *n,
-            VarRetrieve(x) => *self
+            NumberLiteral(n) => Value::Integer(*n),
+            FloatLiteral(f) => Value::Float(*f),
+            StringLiteral(s) => Value::String(s.clone()),
+            VarRetrieve(x) => self
                 .variables
                 .get(x)
-                .ok_or(InterpreterError::UndeclaredVariable(x.clone()))?,
+                .ok_or(InterpreterError::UndeclaredVariable(x.clone()))?
+                .clone(),
+            ArrayRetrieve { name, indices } => {
+                let offset = self.array_offset(name, indices, node.position)?;
+                self.arrays[name].values[offset].clone()
+            }
+            Call { name, args } if is_user_function(name) => {
+                self.call_function(name, args, node.position)?
+            }
+            Call { name, args } => self.call_builtin(name, args, node.position)?,
         };
         Ok(value)
     }
 
-    fn interpret_statement(&mut self, statement: &Statement, output: &mut dyn Write,
-) -> Result<()> {
-        match statement {
+    /// Calculates an expression which has to result in a number
+    fn calculate_number(&mut self, node: &Node<Expression>) -> Result<Value> {
+        match self.calculate_expression(node)? {
+            Value::String(s) => Err(type_mismatch("number", &Value::String(s), node.position)),
+            number => Ok(number),
+        }
+    }
+
+    /// Calculates an expression which has to result in a whole number,
+    /// floats are truncated and an overflow if they are out of range
+    fn calculate_integer(&mut self, node: &Node<Expression>) -> Result<isize> {
+        let number = self.calculate_number(node)?;
+        truncate(&number).ok_or_else(|| self.overflow(node.position))
+    }
+
+    /// Calculates an expression which has to result in a string
+    fn calculate_string(&mut self, node: &Node<Expression>) -> Result<String> {
+        match self.calculate_expression(node)? {
+            Value::String(s) => Ok(s),
+            number => Err(type_mismatch("string", &number, node.position)),
+        }
+    }
+
+    /// Reads the values for an INPUT statement, like classic BASIC it asks
+    /// again if a number is malformed
+    fn read_input(
+        &mut self,
+        input_stmt: &InputStatement,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        let InputStatement { prompt, targets } = input_stmt;
+
+        let values = loop {
+            write!(output, "{}? ", prompt.as_deref().unwrap_or_default())?;
+            output.flush()?;
+
+            let mut fields: Vec<String> = Vec::new();
+            while fields.len() < targets.len() {
+                if !fields.is_empty() {
+                    write!(output, "?? ")?;
+                    output.flush()?;
+                }
+                let mut line = String::new();
+                if input.read_line(&mut line)? == 0 {
+                    return Err(InterpreterError::EndOfInput);
+                }
+                fields.extend(
+                    line.trim_end_matches(['\r', '\n'])
+                        .split(',')
+                        .map(String::from),
+                );
+            }
+            if fields.len() > targets.len() {
+                writeln!(output, "?EXTRA IGNORED")?;
+            }
+
+            let values = targets
+                .iter()
+                .zip(&fields)
+                .map(|(target, field)| Value::parse_for(&target.content.name, field))
+                .collect::<Option<Vec<_>>>();
+
+            match values {
+                Some(values) => break values,
+                None => writeln!(output, "?REDO FROM START")?,
+            }
+        };
+
+        for (target, value) in targets.iter().zip(values) {
+            self.assign(target, value, target.position)?;
+        }
+        Ok(())
+    }
+
+    fn interpret_statement(
+        &mut self,
+        statement: &Node<Statement>,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        match &statement.content {
             Statement::Let(let_stmt) => {
-                let LetStatement { name, expression } = &**let_stmt;
-                let value = self.calculate_expression(&expression.content)?;
-                self.variables.insert(name.clone(), value);
-                self.statement_index += 1;
-            }
-            Statement::GoTo(expression) | Statement::GoSub(expression) => {
-                let line_id = self.calculate_expression(expression)?;
-                if line_id < 0 {
-                    return Err(InterpreterError::InvalidGoto(line_id));
-                }
-                let new_index = self
-                    .program
-                    .iter()
-                    .position(|line| line.line_id == line_id as usize)
-                    .ok_or(InterpreterError::InvalidGoto(line_id))?;
-
-                if let Statement::GoSub { .. } = statement {
-                    self.subroutine_stack.push(self.statement_index + 1);
+                let LetStatement { target, expression } = &**let_stmt;
+                let value = self.calculate_expression(expression)?;
+                self.assign(target, value, expression.position)?;
+                self.statement_index = self.next_statement();
+            }
+            Statement::Dim(declarations) => {
+                for declaration in declarations.iter() {
+                    self.dimension_array(declaration)?;
+                }
+                self.statement_index = self.next_statement();
+            }
+            Statement::Input(input_stmt) => {
+                self.read_input(input_stmt, input, output)?;
+                self.statement_index = self.next_statement();
+            }
+            Statement::GoTo(expression) => self.jump_to_line(expression, JumpKind::GoTo)?,
+            Statement::GoSub(expression) => self.jump_to_line(expression, JumpKind::GoSub)?,
+            Statement::On(on_statement) => {
+                let OnStatement {
+                    selector,
+                    kind,
+                    targets,
+                } = &**on_statement;
+                let selected = self.calculate_integer(selector)?;
+                let target = usize::try_from(selected)
+                    .ok()
+                    .and_then(|selected| targets.get(selected.checked_sub(1)?));
+                match target {
+                    Some(target) => self.jump_to_line(target, *kind)?,
+                    // Out of range, like classic BASIC continue after the ON
+                    None => self.statement_index = self.next_statement(),
+                }
+            }
+            Statement::Randomize(expression) => {
+                let seed = self.calculate_number(expression)?;
+                self.random = Random::new(seed.as_f64().expect("Seed is a number"));
+                self.statement_index = self.next_statement();
+            }
+            Statement::Def(definition) => {
+                self.functions
+                    .insert(definition.name.clone(), Rc::clone(definition));
+                self.statement_index = self.next_statement();
+            }
+            Statement::Data(_) => self.statement_index = self.next_statement(),
+            Statement::Read(targets) => {
+                self.read_data(targets)?;
+                self.statement_index = self.next_statement();
+            }
+            Statement::Restore(line_id) => {
+                self.restore_data(line_id.as_deref())?;
+                self.statement_index = self.next_statement();
+            }
+            Statement::OnError(line_id) => {
+                self.set_error_handler(line_id)?;
+                self.statement_index = self.next_statement();
+            }
+            Statement::Resume(target) => self.resume(target)?,
+            Statement::End => self.state = ProgramState::Finished,
+            Statement::Stop => {
+                self.statement_index = self.next_statement();
+                self.state = ProgramState::Stopped {
+                    line_id: self.current_line,
                 };
-                self.statement_index = new_index;
             }
             Statement::Return => {
                 let index = self
@@ -163,70 +680,417 @@ impl Interpreter {
                 let printables = &**node_printable;
                 let mut out_text = Vec::new();
                 for Node { content, .. } in printables {
-                    match content {
-                        Printable::String(s) => {
-                            out_text.push(s.clone());
-                        }
-                        Printable::ExpressionNode(expression) => {
-                            let v: isize = self.calculate_expression(expression)?;
-                            out_text.push(v.to_string());
-                        }
-                    }
+                    let Printable::ExpressionNode(expression) = content;
+                    let value = self.calculate_expression(expression)?;
+                    out_text.push(value.to_string());
                 }
                 let out_str = out_text.join("\t");
                 writeln!(output, "{out_str}")?;
                 output.flush()?;
-                self.statement_index += 1;
+                self.statement_index = self.next_statement();
+            }
+            Statement::For(for_stmt) => {
+                let ForStatement {
+                    variable,
+                    start,
+                    end,
+                    step,
+                } = &**for_stmt;
+                if value::is_string_name(variable) {
+                    return Err(InterpreterError::TypeMismatch {
+                        expected: "number",
+                        actual: "string",
+                        position: start.position,
+                    });
+                }
+                let start = self.calculate_number(start)?;
+                let end = self.calculate_number(end)?;
+                let step = match step {
+                    Some(step) => self.calculate_number(step)?,
+                    None => Value::Integer(1),
+                };
+
+                // Re-entering a loop (e.g. via GOTO) discards its old frame and all inner ones
+                if let Some(index) = self.loop_stack.iter().position(|f| f.variable == *variable) {
+                    self.loop_stack.truncate(index);
+                }
+
+                // Like on the C64 the body is always executed at least once
+                self.variables.insert(variable.clone(), start);
+                self.statement_index = self.next_statement();
+                self.loop_stack.push(ForFrame {
+                    variable: variable.clone(),
+                    end,
+                    step,
+                    body_index: self.statement_index,
+                });
+            }
+            Statement::Next(variable) => {
+                let frame = self
+                    .loop_stack
+                    .last()
+                    .ok_or(InterpreterError::NextWithoutFor)?;
+
+                if let Some(variable) = variable
+                    && *variable != frame.variable
+                {
+                    return Err(InterpreterError::MismatchedNext {
+                        expected: frame.variable.clone(),
+                        actual: variable.clone(),
+                    });
+                }
+
+                let value = self
+                    .variables
+                    .get(&frame.variable)
+                    .ok_or_else(|| InterpreterError::UndeclaredVariable(frame.variable.clone()))?
+                    .clone();
+                let value = numeric_operation(BinaryOperator::Plus, value, frame.step.clone())
+                    .ok_or_else(|| self.overflow(statement.position))?;
+                let ordering = value.compare(&frame.end).expect("Loop values are numbers");
+                self.variables.insert(frame.variable.clone(), value);
+
+                let continue_loop = if frame.step.as_f64() >= Some(0.0) {
+                    ordering != Ordering::Greater
+                } else {
+                    ordering != Ordering::Less
+                };
+
+                if continue_loop {
+                    self.statement_index = frame.body_index;
+                } else {
+                    self.loop_stack.pop();
+                    self.statement_index = self.next_statement();
+                }
             }
             Statement::If(if_statement) => {
                 let IfStatement {
                     boolean_expr,
                     then_statement,
+                    else_target,
                 } = &**if_statement;
                 let condition = self.calculate_boolean_expression(&boolean_expr.content)?;
-                if condition == true {
-                    self.interpret_statement(&then_statement.content, output)?;
+                match (condition, then_statement) {
+                    (true, Some(then_statement)) => {
+                        self.interpret_statement(then_statement, input, output)?;
+                    }
+                    (true, None) => self.statement_index = self.next_statement(),
+                    (false, _) => self.enter_else(*else_target)?,
+                }
+            }
+            Statement::ElseIf(else_if) => {
+                // The previous branch is finished
+                self.jump_to(else_if.end_target);
+            }
+            Statement::Else(end_target) => self.jump_to(*end_target),
+            Statement::EndIf => self.statement_index = self.next_statement(),
+            Statement::While(while_stmt) => {
+                if self.calculate_boolean_expression(&while_stmt.condition.content)? {
+                    self.statement_index = self.next_statement();
                 } else {
-                    self.statement_index += 1;
+                    self.jump_after(while_stmt.end_target);
+                }
+            }
+            Statement::Wend(start_target) => self.jump_to(*start_target),
+            Statement::Do(do_stmt) => {
+                if self.loop_condition_holds(&do_stmt.condition)? {
+                    self.statement_index = self.next_statement();
+                } else {
+                    self.jump_after(do_stmt.end_target);
+                }
+            }
+            Statement::Loop(loop_stmt) => {
+                // The DO checks its own condition again
+                if self.loop_condition_holds(&loop_stmt.condition)? {
+                    self.jump_to(loop_stmt.start_target);
+                } else {
+                    self.statement_index = self.next_statement();
                 }
             }
         }
         Ok(())
     }
 
-    pub fn finished(&self) -> bool {
-        return self.statement_index >= self.program.len();
+    /// Continues after a false condition: at the next branch whose ELSEIF
+    /// condition is true, after an ELSE or END IF, or on the next line if
+    /// there is no target
+    fn enter_else(&mut self, mut target: Option<StatementIndex>) -> Result<()> {
+        while let Some(index) = target {
+            let statement = self.program[index.line].statements[index.statement].clone();
+            if let Statement::ElseIf(else_if) = &statement.content {
+                let ElseIfStatement {
+                    boolean_expr,
+                    else_target,
+                    ..
+                } = &**else_if;
+                if !self.calculate_boolean_expression(&boolean_expr.content)? {
+                    target = *else_target;
+                    continue;
+                }
+            }
+            self.jump_after(Some(index));
+            return Ok(());
+        }
+
+        self.jump_to(None);
+        Ok(())
     }
 
-    pub fn current_line(&self) -> usize {
-        return self.current_line;
+    /// Jumps to the line given by `expression`, GOSUB remembers the following
+    /// statement for RETURN
+    fn jump_to_line(&mut self, expression: &Node<Expression>, kind: JumpKind) -> Result<()> {
+        let line_id = self.calculate_integer(expression)?;
+        let new_index = self.line_index_of(line_id)?;
+
+        if kind == JumpKind::GoSub {
+            self.subroutine_stack.push(self.next_statement());
+        }
+        self.statement_index = StatementIndex {
+            line: new_index,
+            statement: 0,
+        };
+        Ok(())
     }
 
+    /// Index in `program` of the line with the number `line_id`
+    fn line_index_of(&self, line_id: isize) -> Result<usize> {
+        usize::try_from(line_id)
+            .ok()
+            .and_then(|line_id| self.line_index.get(&line_id))
+            .copied()
+            .ok_or(InterpreterError::InvalidGoto(line_id))
+    }
 
-    pub fn ast_json_pretty(self) -> Result<String>{
-        serde_json::to_string_pretty(&self.program).map_err(InterpreterError::ExportError)
+    /// Continues at a target resolved by the parser, like the END IF of a
+    /// block. Without a target the rest of the line is skipped.
+    fn jump_to(&mut self, target: Option<StatementIndex>) {
+        self.statement_index = target.unwrap_or(StatementIndex {
+            line: self.statement_index.line + 1,
+            statement: 0,
+        });
+    }
+
+    /// Continues after a target resolved by the parser, like the WEND of a loop
+    fn jump_after(&mut self, target: Option<StatementIndex>) {
+        match target {
+            Some(target) => self.statement_index = self.statement_after(target),
+            None => self.jump_to(None),
+        }
+    }
+
+    /// Checks the optional WHILE or UNTIL condition of DO and LOOP
+    fn loop_condition_holds(&mut self, condition: &Option<LoopCondition>) -> Result<bool> {
+        match condition {
+            None => Ok(true),
+            Some(LoopCondition::While(condition)) => {
+                self.calculate_boolean_expression(&condition.content)
+            }
+            Some(LoopCondition::Until(condition)) => {
+                Ok(!self.calculate_boolean_expression(&condition.content)?)
+            }
+        }
+    }
+
+    /// The statement after the current one, which may be on the next line
+    fn next_statement(&self) -> StatementIndex {
+        self.statement_after(self.statement_index)
+    }
+
+    fn statement_after(&self, index: StatementIndex) -> StatementIndex {
+        let StatementIndex { line, statement } = index;
+        if statement + 1 < self.program[line].statements.len() {
+            StatementIndex {
+                line,
+                statement: statement + 1,
+            }
+        } else {
+            StatementIndex {
+                line: line + 1,
+                statement: 0,
+            }
+        }
+    }
+
+    /// True if the program does not continue: it is finished, stopped or
+    /// aborted by an error, see `state`
+    pub fn finished(&self) -> bool {
+        self.state != ProgramState::Running
+    }
+
+    pub fn state(&self) -> ProgramState {
+        self.state
     }
 
-    // Executes a signle line of the program
-    pub fn step_line(&mut self, output: &mut dyn Write,
-) -> Result<()> {
-        if self.finished() {
-            return Err(InterpreterError::Finished);
+    /// Resumes a program halted by STOP with the statement after the STOP
+    pub fn cont(&mut self) -> Result<()> {
+        let ProgramState::Stopped { .. } = self.state else {
+            return Err(InterpreterError::CannotContinue);
         };
 
-        let Line { statement, line_id } = &self.program[self.statement_index];
-        log::debug!("Intrpreting line: {line_id}");
+        self.state = if self.statement_index.line < self.program.len() {
+            ProgramState::Running
+        } else {
+            ProgramState::Finished
+        };
+        Ok(())
+    }
+
+    pub fn current_line(&self) -> usize {
+        self.current_line
+    }
+
+    pub fn ast_json_pretty(self) -> Result<String> {
+        serde_json::to_string_pretty(&self.program).map_err(InterpreterError::ExportError)
+    }
+
+    /// Executes the statement at the program counter
+    fn step_statement(&mut self, input: &mut dyn BufRead, output: &mut dyn Write) -> Result<()> {
+        let StatementIndex { line, statement } = self.statement_index;
+        let Line {
+            statements,
+            line_id,
+            ..
+        } = &self.program[line];
+        log::debug!("Intrpreting line: {line_id}, statement: {statement}");
         self.current_line = *line_id;
 
-        let content = &statement.clone().content;
-        self.interpret_statement(content, output)
+        let statement = statements[statement].clone();
+        self.interpret_statement(&statement, input, output)
+    }
+
+    /// Executes the statements of a line up to the end of the line or to the
+    /// first jump, INPUT reads from `input`
+    pub fn step_line(&mut self, input: &mut dyn BufRead, output: &mut dyn Write) -> Result<()> {
+        match self.state {
+            ProgramState::Running => (),
+            ProgramState::Stopped { line_id } => {
+                return Err(InterpreterError::Stopped { line_id });
+            }
+            ProgramState::Finished | ProgramState::Errored { .. } => {
+                return Err(InterpreterError::Finished);
+            }
+        }
+
+        loop {
+            let index = self.statement_index;
+            let expected = self.next_statement();
+            if let Err(error) = self.step_statement(input, output) {
+                if self.handle_error(&error, index) {
+                    break;
+                }
+                self.state = ProgramState::Errored {
+                    line_id: self.current_line,
+                };
+                return Err(error);
+            }
+
+            if self.state != ProgramState::Running
+                || self.statement_index != expected
+                || expected.statement == 0
+            {
+                break;
+            }
+        }
+
+        if self.state == ProgramState::Running && self.statement_index.line >= self.program.len() {
+            self.state = ProgramState::Finished;
+        }
+        Ok(())
     }
 
-    pub fn run(&mut self,  output: &mut dyn Write,
-) -> Result<()> {
+    /// Runs the program until it is finished or stopped
+    pub fn run(&mut self, input: &mut dyn BufRead, output: &mut dyn Write) -> Result<()> {
         while !self.finished() {
-            self.step_line(output)?
+            self.step_line(input, output)?
         }
         Ok(())
     }
 }
+
+/// Arithmetic on two numbers: integers stay integers, unless a division has
+/// a remainder. As soon as one operand is a float the result is a float.
+///
+/// `\`, AND, OR and XOR always work on integers, floats are truncated.
+///
+/// Returns `None` on overflow, a division by zero has to be checked before.
+fn numeric_operation(operator: BinaryOperator, left: Value, right: Value) -> Option<Value> {
+    if matches!(
+        operator,
+        BinaryOperator::IntegerDivide
+            | BinaryOperator::And
+            | BinaryOperator::Or
+            | BinaryOperator::Xor
+    ) {
+        let (left, right) = (truncate(&left)?, truncate(&right)?);
+        let result = match operator {
+            BinaryOperator::IntegerDivide => left.checked_div(right)?,
+            BinaryOperator::And => left & right,
+            BinaryOperator::Or => left | right,
+            _ => left ^ right,
+        };
+        return Some(Value::Integer(result));
+    }
+
+    if let (Value::Integer(left), Value::Integer(right)) = (&left, &right) {
+        let (left, right) = (*left, *right);
+        match operator {
+            BinaryOperator::Plus => return left.checked_add(right).map(Value::Integer),
+            BinaryOperator::Minus => return left.checked_sub(right).map(Value::Integer),
+            BinaryOperator::Multiply => return left.checked_mul(right).map(Value::Integer),
+            BinaryOperator::Modulo => return left.checked_rem(right).map(Value::Integer),
+            BinaryOperator::Devide if left.checked_rem(right)? == 0 => {
+                return left.checked_div(right).map(Value::Integer);
+            }
+            // Negative exponents give fractions
+            BinaryOperator::Power if right >= 0 => {
+                let result = match u32::try_from(right) {
+                    Ok(exponent) => left.checked_pow(exponent),
+                    // Only these bases don't overflow with such a large exponent
+                    Err(_) => match left {
+                        0 | 1 => Some(left),
+                        -1 if right % 2 == 0 => Some(1),
+                        -1 => Some(-1),
+                        _ => None,
+                    },
+                };
+                return result.map(Value::Integer);
+            }
+            _ => {}
+        }
+    }
+
+    let left = left.as_f64().expect("Operand is a number");
+    let right = right.as_f64().expect("Operand is a number");
+    let result = match operator {
+        BinaryOperator::Plus => left + right,
+        BinaryOperator::Minus => left - right,
+        BinaryOperator::Multiply => left * right,
+        BinaryOperator::Devide => left / right,
+        BinaryOperator::Modulo => left % right,
+        BinaryOperator::Power => left.powf(right),
+        _ => unreachable!("Integer operations are handled above"),
+    };
+    result.is_finite().then_some(Value::Float(result))
+}
+
+/// Number without its fraction, `None` if it is out of the integer range
+fn truncate(value: &Value) -> Option<isize> {
+    match value {
+        Value::Integer(n) => Some(*n),
+        Value::Float(f) => {
+            let truncated = f.trunc();
+            // isize::MAX as f64 rounds up, so it is already out of range
+            (truncated >= isize::MIN as f64 && truncated < isize::MAX as f64)
+                .then_some(truncated as isize)
+        }
+        Value::String(_) => None,
+    }
+}
+
+fn type_mismatch(expected: &'static str, value: &Value, position: Position) -> InterpreterError {
+    InterpreterError::TypeMismatch {
+        expected,
+        actual: value.type_name(),
+        position,
+    }
+}
@@ -0,0 +1,275 @@
+use super::random::Random;
+use super::value::Value;
+use super::{Interpreter, InterpreterError, Result};
+use crate::parser::Node;
+use crate::parser::builtins::find_builtin;
+use crate::parser::expressions::{BinaryOperator, Expression};
+use crate::parser::tokenizer::Position;
+use std::cmp::Ordering;
+
+impl Interpreter {
+    /// Calls the built-in function `name`, `position` is the position of the call
+    pub(super) fn call_builtin(
+        &mut self,
+        name: &str,
+        args: &[Node<Expression>],
+        position: Position,
+    ) -> Result<Value> {
+        let builtin = find_builtin(name).ok_or_else(|| InterpreterError::UnknownFunction {
+            name: name.to_string(),
+            position,
+        })?;
+        // The parser checks this too, but an AST may also be built by hand
+        builtin.check_arity(args.len(), position)?;
+
+        let value = match builtin.name {
+            "ABS" => match self.calculate_number(&args[0])? {
+                Value::Integer(n) => {
+                    Value::Integer(n.checked_abs().ok_or_else(|| self.overflow(position))?)
+                }
+                Value::Float(f) => Value::Float(f.abs()),
+                Value::String(_) => unreachable!("Checked by calculate_number"),
+            },
+            "SGN" => {
+                let number = self.calculate_number(&args[0])?;
+                let sign = match number.compare(&Value::Integer(0)) {
+                    Some(Ordering::Less) => -1,
+                    Some(Ordering::Greater) => 1,
+                    _ => 0,
+                };
+                Value::Integer(sign)
+            }
+            "INT" => match self.calculate_number(&args[0])? {
+                Value::Float(f) => float_to_value(f.floor()),
+                integer => integer,
+            },
+            "SQR" => {
+                let node = &args[0];
+                let number = self.calculate_number(node)?.as_f64();
+                match number {
+                    Some(f) if f >= 0.0 => Value::Float(f.sqrt()),
+                    _ => return Err(invalid_argument("SQR", "negative number", node.position)),
+                }
+            }
+            "MOD" => {
+                let left = self.calculate_number(&args[0])?;
+                let right = self.calculate_number(&args[1])?;
+                self.arithmetic(BinaryOperator::Modulo, left, right, position)?
+            }
+            "MIN" | "MAX" => {
+                let wanted = if builtin.name == "MIN" {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+                let mut result = self.calculate_number(&args[0])?;
+                for arg in &args[1..] {
+                    let number = self.calculate_number(arg)?;
+                    if number.compare(&result) == Some(wanted) {
+                        result = number;
+                    }
+                }
+                result
+            }
+            "RND" => {
+                // Like on the C64: a negative argument seeds the generator,
+                // zero repeats the last number
+                let argument = match args.first() {
+                    Some(node) => self.calculate_number(node)?.as_f64(),
+                    None => None,
+                };
+                match argument {
+                    Some(seed) if seed < 0.0 => {
+                        self.random = Random::new(seed);
+                        Value::Float(self.random.next_number())
+                    }
+                    Some(0.0) => Value::Float(self.random.last()),
+                    _ => Value::Float(self.random.next_number()),
+                }
+            }
+            "ERR" => Value::Integer(self.error_handling.code as isize),
+            "ERL" => Value::Integer(self.error_handling.line_id as isize),
+            name => self.call_string_function(name, args)?,
+        };
+        Ok(value)
+    }
+
+    /// Calls one of the string functions, strings are indexed by characters
+    /// starting at 1
+    fn call_string_function(
+        &mut self,
+        name: &'static str,
+        args: &[Node<Expression>],
+    ) -> Result<Value> {
+        let value = match name {
+            "LEN" => {
+                let text = self.calculate_string(&args[0])?;
+                Value::Integer(text.chars().count() as isize)
+            }
+            "LEFT$" => {
+                let text = self.calculate_string(&args[0])?;
+                let count = self.length_argument(name, &args[1])?;
+                Value::String(text.chars().take(count).collect())
+            }
+            "RIGHT$" => {
+                let text = self.calculate_string(&args[0])?;
+                let count = self.length_argument(name, &args[1])?;
+                let skip = text.chars().count().saturating_sub(count);
+                Value::String(text.chars().skip(skip).collect())
+            }
+            "MID$" => {
+                let text = self.calculate_string(&args[0])?;
+                let start = self.start_argument(name, &args[1])?;
+                let rest = text.chars().skip(start - 1);
+                match args.get(2) {
+                    Some(node) => {
+                        let count = self.length_argument(name, node)?;
+                        Value::String(rest.take(count).collect())
+                    }
+                    None => Value::String(rest.collect()),
+                }
+            }
+            "CHR$" => {
+                let node = &args[0];
+                let code = self.calculate_integer(node)?;
+                let character = u32::try_from(code)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid_argument(name, "not a character code", node.position))?;
+                Value::String(character.to_string())
+            }
+            "ASC" => {
+                let node = &args[0];
+                let text = self.calculate_string(node)?;
+                let character = text
+                    .chars()
+                    .next()
+                    .ok_or_else(|| invalid_argument(name, "empty string", node.position))?;
+                Value::Integer(character as isize)
+            }
+            "STR$" => Value::String(self.calculate_number(&args[0])?.to_string()),
+            "VAL" => {
+                let node = &args[0];
+                let text = self.calculate_string(node)?;
+                parse_number_prefix(&text).ok_or_else(|| self.overflow(node.position))?
+            }
+            "INSTR" => {
+                let (start, text_node, search_node) = match args {
+                    [start, text, search] => (self.start_argument(name, start)?, text, search),
+                    [text, search] => (1, text, search),
+                    _ => unreachable!("Arity was checked"),
+                };
+                let text = self
+                    .calculate_string(text_node)?
+                    .chars()
+                    .collect::<Vec<_>>();
+                let search = self
+                    .calculate_string(search_node)?
+                    .chars()
+                    .collect::<Vec<_>>();
+
+                let last = text.len().checked_sub(search.len());
+                let found = last.and_then(|last| {
+                    (start - 1..=last).find(|&i| text[i..i + search.len()] == search[..])
+                });
+                Value::Integer(found.map_or(0, |i| i as isize + 1))
+            }
+            _ => unreachable!("Every built-in function is implemented"),
+        };
+        Ok(value)
+    }
+
+    /// Number of characters, must not be negative
+    fn length_argument(
+        &mut self,
+        function: &'static str,
+        node: &Node<Expression>,
+    ) -> Result<usize> {
+        let count = self.calculate_integer(node)?;
+        usize::try_from(count)
+            .map_err(|_| invalid_argument(function, "negative length", node.position))
+    }
+
+    /// Position of a character, the first character is at 1
+    fn start_argument(&mut self, function: &'static str, node: &Node<Expression>) -> Result<usize> {
+        let start = self.calculate_integer(node)?;
+        match usize::try_from(start) {
+            Ok(start) if start >= 1 => Ok(start),
+            _ => Err(invalid_argument(
+                function,
+                "start must be at least 1",
+                node.position,
+            )),
+        }
+    }
+}
+
+/// Value of the number at the start of a text like VAL in classic BASIC,
+/// leading spaces are skipped and 0 is returned if there is no number.
+/// `None` if the number is too large for a float
+fn parse_number_prefix(text: &str) -> Option<Value> {
+    let text = text.trim_start();
+    let bytes = text.as_bytes();
+    let digits_from = |start: usize| {
+        start
+            + bytes[start..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count()
+    };
+
+    let mut end = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+    let integer_end = digits_from(end);
+    let mut mantissa_digits = integer_end - end;
+    end = integer_end;
+    if bytes.get(end) == Some(&b'.') {
+        let fraction_end = digits_from(end + 1);
+        mantissa_digits += fraction_end - end - 1;
+        end = fraction_end;
+    }
+    if mantissa_digits == 0 {
+        return Some(Value::Integer(0));
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(bytes.get(end + 1), Some(b'+' | b'-')));
+        let exponent_end = digits_from(end + 1 + sign);
+        // An exponent without digits is not part of the number
+        if exponent_end > end + 1 + sign {
+            end = exponent_end;
+        }
+    }
+
+    // The prefix is ASCII, so `end` is a char boundary
+    let number = &text[..end];
+    if end == integer_end
+        && let Ok(n) = number.parse()
+    {
+        return Some(Value::Integer(n));
+    }
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|f| f.is_finite())
+        .map(Value::Float)
+}
+
+/// Converts a whole float to an integer if it is small enough
+fn float_to_value(f: f64) -> Value {
+    if f >= isize::MIN as f64 && f < isize::MAX as f64 {
+        Value::Integer(f as isize)
+    } else {
+        Value::Float(f)
+    }
+}
+
+fn invalid_argument(
+    function: &'static str,
+    reason: &'static str,
+    position: Position,
+) -> InterpreterError {
+    InterpreterError::InvalidArgument {
+        function,
+        reason,
+        position,
+    }
+}
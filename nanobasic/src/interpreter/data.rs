@@ -0,0 +1,85 @@
+use super::value::Value;
+use super::{Interpreter, InterpreterError, Result, type_mismatch};
+use crate::parser::expressions::Expression;
+use crate::parser::statements::Statement;
+use crate::parser::statements::data_statement::Constant;
+use crate::parser::statements::let_statment::LetTarget;
+use crate::parser::tokenizer::Position;
+use crate::parser::{Line, Node};
+
+/// A value of a DATA statement
+pub(super) struct DataItem {
+    line_id: usize,
+    value: Value,
+    position: Position,
+}
+
+/// Collects the values of all DATA statements in program order, including
+/// the ones following THEN
+pub(super) fn collect_data(program: &[Line]) -> Vec<DataItem> {
+    let mut data = Vec::new();
+    for line in program {
+        let statements = line
+            .statements
+            .iter()
+            .flat_map(|statement| statement.content.with_then_statements());
+        for statement in statements {
+            if let Statement::Data(constants) = statement {
+                data.extend(constants.iter().map(|constant| DataItem {
+                    line_id: line.line_id,
+                    value: match &constant.content {
+                        Constant::Integer(n) => Value::Integer(*n),
+                        Constant::Float(f) => Value::Float(*f),
+                        Constant::String(s) => Value::String(s.clone()),
+                    },
+                    position: constant.position,
+                }));
+            }
+        }
+    }
+    data
+}
+
+impl Interpreter {
+    /// Assigns the next DATA values to the targets of a READ statement
+    pub(super) fn read_data(&mut self, targets: &[Node<LetTarget>]) -> Result<()> {
+        for target in targets {
+            let item = self
+                .data
+                .get(self.data_pointer)
+                .ok_or(InterpreterError::OutOfData {
+                    line_id: self.current_line,
+                })?;
+            if !item.value.fits_name(&target.content.name) {
+                let expected = Value::default_for(&target.content.name).type_name();
+                return Err(type_mismatch(expected, &item.value, item.position));
+            }
+
+            let value = item.value.clone();
+            self.data_pointer += 1;
+            self.assign(target, value, target.position)?;
+        }
+        Ok(())
+    }
+
+    /// Moves the data pointer to the first value, or to the first value at
+    /// or after the line `line_id`
+    pub(super) fn restore_data(&mut self, line_id: Option<&Node<Expression>>) -> Result<()> {
+        self.data_pointer = match line_id {
+            None => 0,
+            Some(expression) => {
+                let line_id = self.calculate_integer(expression)?;
+                let known = usize::try_from(line_id)
+                    .is_ok_and(|line_id| self.line_index.contains_key(&line_id));
+                if !known {
+                    return Err(InterpreterError::InvalidRestore(line_id));
+                }
+                self.data
+                    .iter()
+                    .position(|item| item.line_id as isize >= line_id)
+                    .unwrap_or(self.data.len())
+            }
+        };
+        Ok(())
+    }
+}
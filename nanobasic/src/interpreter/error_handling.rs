@@ -0,0 +1,107 @@
+use super::{Interpreter, InterpreterError, Result};
+use crate::parser::Node;
+use crate::parser::StatementIndex;
+use crate::parser::expressions::Expression;
+use crate::parser::statements::on_statement::JumpKind;
+use crate::parser::statements::resume_statement::ResumeTarget;
+
+/// State of ON ERROR GOTO
+#[derive(Debug, Default)]
+pub(super) struct ErrorHandling {
+    /// Line index of the handler, `None` if errors abort the program
+    handler: Option<usize>,
+    /// Statement which failed while the handler runs, RESUME continues there
+    failed_statement: Option<StatementIndex>,
+    /// Code of the last handled error, returned by ERR
+    pub(super) code: usize,
+    /// Line number of the last handled error, returned by ERL
+    pub(super) line_id: usize,
+}
+
+impl InterpreterError {
+    /// Stable number of the error, returned by ERR. Most numbers are the ones
+    /// of Microsoft BASIC. `None` for errors which can not be handled by
+    /// ON ERROR GOTO because they do not come from a running statement.
+    pub fn code(&self) -> Option<usize> {
+        use InterpreterError::*;
+        let code = match self {
+            NextWithoutFor | MismatchedNext { .. } => 1,
+            ReturnWithoutGosub => 3,
+            OutOfData { .. } => 4,
+            InvalidArgument { .. } | WrongNumberOfArguments { .. } => 5,
+            Overflow { .. } => 6,
+            CallTooDeep { .. } | ArrayTooLarge { .. } => 7,
+            InvalidGoto(_) | InvalidRestore(_) => 8,
+            UndimensionedArray { .. } | IndexOutOfRange { .. } | WrongNumberOfIndices { .. } => 9,
+            RedimensionedArray { .. } => 10,
+            DivisionByZero { .. } => 11,
+            TypeMismatch { .. } => 13,
+            UnknownFunction { .. } => 18,
+            ResumeWithoutError => 20,
+            OutputError(_) => 57,
+            EndOfInput => 62,
+            UndeclaredVariable(_) => 100,
+            ParseErrorError(_) | Finished | Stopped { .. } | CannotContinue | ExportError(_) => {
+                return None;
+            }
+        };
+        Some(code)
+    }
+}
+
+impl Interpreter {
+    /// Sets the handler to the line given by `line_id`, 0 removes the handler
+    pub(super) fn set_error_handler(&mut self, line_id: &Node<Expression>) -> Result<()> {
+        let line_id = self.calculate_integer(line_id)?;
+        self.error_handling.handler = match line_id {
+            0 => None,
+            _ => Some(self.line_index_of(line_id)?),
+        };
+        Ok(())
+    }
+
+    /// Continues at the handler if there is one and no other error is being
+    /// handled. Returns false if the error has to abort the program.
+    pub(super) fn handle_error(
+        &mut self,
+        error: &InterpreterError,
+        failed: StatementIndex,
+    ) -> bool {
+        let ErrorHandling {
+            handler: Some(handler),
+            failed_statement: None,
+            ..
+        } = self.error_handling
+        else {
+            return false;
+        };
+        let Some(code) = error.code() else {
+            return false;
+        };
+
+        self.error_handling.failed_statement = Some(failed);
+        self.error_handling.code = code;
+        self.error_handling.line_id = self.current_line;
+        self.statement_index = StatementIndex {
+            line: handler,
+            statement: 0,
+        };
+        true
+    }
+
+    /// Ends the handling of an error
+    pub(super) fn resume(&mut self, target: &ResumeTarget) -> Result<()> {
+        let failed = self
+            .error_handling
+            .failed_statement
+            .take()
+            .ok_or(InterpreterError::ResumeWithoutError)?;
+
+        match target {
+            ResumeTarget::Retry => self.statement_index = failed,
+            ResumeTarget::Next => self.statement_index = self.statement_after(failed),
+            ResumeTarget::Line(line_id) => self.jump_to_line(line_id, JumpKind::GoTo)?,
+        }
+        Ok(())
+    }
+}
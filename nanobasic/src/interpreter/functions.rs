@@ -0,0 +1,81 @@
+use super::value::Value;
+use super::{Interpreter, InterpreterError, Result, type_mismatch};
+use crate::parser::Node;
+use crate::parser::expressions::Expression;
+use crate::parser::tokenizer::Position;
+
+/// Maximum number of nested calls of user-defined functions
+const MAX_CALL_DEPTH: usize = 100;
+
+impl Interpreter {
+    /// Calls the user-defined function `name`. The parameters shadow the
+    /// variables with the same names while the body is evaluated, all other
+    /// variables are the global ones.
+    pub(super) fn call_function(
+        &mut self,
+        name: &str,
+        args: &[Node<Expression>],
+        position: Position,
+    ) -> Result<Value> {
+        let definition =
+            self.functions
+                .get(name)
+                .cloned()
+                .ok_or_else(|| InterpreterError::UnknownFunction {
+                    name: name.to_string(),
+                    position,
+                })?;
+
+        if args.len() != definition.params.len() {
+            return Err(InterpreterError::WrongNumberOfArguments {
+                name: name.to_string(),
+                expected: definition.params.len(),
+                actual: args.len(),
+                position,
+            });
+        }
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(InterpreterError::CallTooDeep {
+                name: name.to_string(),
+                position,
+            });
+        }
+
+        // The arguments are evaluated before any parameter is bound
+        let mut values = Vec::with_capacity(args.len());
+        for (param, arg) in definition.params.iter().zip(args) {
+            let value = self.calculate_expression(arg)?;
+            if !value.fits_name(param) {
+                let expected = Value::default_for(param).type_name();
+                return Err(type_mismatch(expected, &value, arg.position));
+            }
+            values.push(value);
+        }
+
+        let shadowed = definition
+            .params
+            .iter()
+            .zip(values)
+            .map(|(param, value)| (param, self.variables.insert(param.clone(), value)))
+            .collect::<Vec<_>>();
+
+        self.call_depth += 1;
+        let result = self.calculate_expression(&definition.body);
+        self.call_depth -= 1;
+
+        // Restored even if the body failed
+        for (param, value) in shadowed {
+            match value {
+                Some(value) => self.variables.insert(param.clone(), value),
+                None => self.variables.remove(param),
+            };
+        }
+
+        let value = result?;
+        if !value.fits_name(name) {
+            let expected = Value::default_for(name).type_name();
+            return Err(type_mismatch(expected, &value, definition.body.position));
+        }
+        Ok(value)
+    }
+}
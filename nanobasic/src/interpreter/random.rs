@@ -0,0 +1,61 @@
+/// Deterministic pseudo random numbers (xorshift64*), the same seed always
+/// gives the same sequence of numbers
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+    last: f64,
+}
+
+impl Random {
+    pub fn new(seed: f64) -> Self {
+        // Mix the bits of the seed (splitmix64), similar seeds give different
+        // sequences and the state is never zero
+        let mut z = seed.to_bits().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        Random {
+            state: if z == 0 { 1 } else { z },
+            last: 0.0,
+        }
+    }
+
+    /// Next number in [0, 1)
+    pub fn next_number(&mut self) -> f64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let bits = self.state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11;
+        self.last = bits as f64 / (1u64 << 53) as f64;
+        self.last
+    }
+
+    /// The number returned by the last call of `next_number`, 0 before the first call
+    pub fn last(&self) -> f64 {
+        self.last
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Random::new(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Random;
+
+    #[test]
+    fn test_random_is_reproducible() {
+        let numbers = |seed| {
+            let mut random = Random::new(seed);
+            (0..100).map(|_| random.next_number()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(numbers(42.0), numbers(42.0));
+        assert_ne!(numbers(42.0), numbers(43.0));
+        assert!(numbers(1.0).iter().all(|n| (0.0..1.0).contains(n)));
+    }
+}
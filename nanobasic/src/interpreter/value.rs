@@ -0,0 +1,137 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Runtime value of an expression or a variable
+///
+/// Numbers are either integers or floats. Like in BASIC, integers are
+/// promoted to floats as soon as one operand of an operation is a float.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(isize),
+    Float(f64),
+    String(String),
+}
+
+impl Value {
+    /// Default value of a variable or array element with the given name,
+    /// names ending with '$' hold strings
+    pub fn default_for(name: &str) -> Self {
+        if is_string_name(name) {
+            Value::String(String::new())
+        } else {
+            Value::Integer(0)
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Integer(_) | Value::Float(_) => "number",
+            Value::String(_) => "string",
+        }
+    }
+
+    /// Converts text entered by the user into a value for the variable with
+    /// the given name, returns `None` if a number is expected but not found
+    pub fn parse_for(name: &str, text: &str) -> Option<Self> {
+        let text = text.trim();
+        if is_string_name(name) {
+            Some(Value::String(text.to_string()))
+        } else if let Ok(n) = text.parse() {
+            Some(Value::Integer(n))
+        } else {
+            text.parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .map(Value::Float)
+        }
+    }
+
+    /// Checks if the value may be stored in a variable with the given name
+    pub fn fits_name(&self, name: &str) -> bool {
+        matches!(self, Value::String(_)) == is_string_name(name)
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Integer(n) => Some(*n as f64),
+            Value::Float(f) => Some(*f),
+            Value::String(_) => None,
+        }
+    }
+
+    /// Compares two numbers or two strings, `None` if the types differ
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Integer(l), Value::Integer(r)) => Some(l.cmp(r)),
+            (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+            (l, r) => l.as_f64()?.partial_cmp(&r.as_f64()?),
+        }
+    }
+}
+
+pub fn is_string_name(name: &str) -> bool {
+    name.ends_with('$')
+}
+
+/// Formats a float independent of the platform: at most 15 significant
+/// digits, very large and very small numbers in E notation
+fn format_float(f: f64) -> String {
+    if f.is_nan() {
+        return "NaN".to_string();
+    }
+    if f.is_infinite() {
+        return if f > 0.0 { "INF" } else { "-INF" }.to_string();
+    }
+
+    // Rounding hides representation errors, so 0.1 + 0.2 prints as 0.3
+    let rounded: f64 = format!("{f:.14e}")
+        .parse()
+        .expect("Formatted float is valid");
+    let magnitude = rounded.abs();
+    if magnitude == 0.0 {
+        "0".to_string()
+    } else if (1e-5..1e15).contains(&magnitude) {
+        format!("{rounded}")
+    } else {
+        let text = format!("{rounded:E}");
+        match text.split_once('E') {
+            Some((mantissa, exponent)) if !exponent.starts_with('-') => {
+                format!("{mantissa}E+{exponent}")
+            }
+            _ => text,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Integer(n) => write!(f, "{n}"),
+            Value::Float(x) => write!(f, "{}", format_float(*x)),
+            Value::String(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+
+    #[test]
+    fn test_format_float() {
+        let params = [
+            (3.0, "3"),
+            (-2.5, "-2.5"),
+            (0.1 + 0.2, "0.3"),
+            (1.0 / 3.0, "0.333333333333333"),
+            (1e-3, "0.001"),
+            (1.5e-7, "1.5E-7"),
+            (2e20, "2E+20"),
+            (-0.0, "0"),
+        ];
+
+        for (number, expected) in params {
+            assert_eq!(Value::Float(number).to_string(), expected);
+        }
+    }
+}
@@ -1,9 +1,12 @@
+pub mod builtins;
 pub mod expressions;
 pub mod statements;
 pub mod tokenizer;
+pub mod validation;
 
-use self::tokenizer::{Position, Token, TokenType, tokenize};
+use self::tokenizer::{KeywordMode, Position, Token, TokenType, tokenize_with};
 use crate::parser::statements::Statement;
+use crate::parser::validation::{LineOrder, validate_lines};
 use serde::Serialize;
 use std::fs::File;
 use std::io;
@@ -29,11 +32,81 @@ pub enum ParseError {
         unkown_code: String,
     },
 
+    #[error("String starting at {position} is not terminated by a quote")]
+    UnterminatedString { position: Position },
+
+    #[error("Number {literal} at {position} is too large")]
+    NumberTooLarge { literal: String, position: Position },
+
     #[error("Unexpected end of file")]
     UnexpectedEOF,
 
     #[error("Wrong Token, expected: {expected}, actual: {actual}")]
     WrongToken { expected: String, actual: String },
+
+    #[error("Unexpected {token} after the end of the statement at {position}")]
+    TrailingToken { token: String, position: Position },
+
+    #[error("{statement} at {position} is not followed by a statement")]
+    MissingStatement {
+        statement: &'static str,
+        position: Position,
+    },
+
+    #[error("{statement} at {position} is not closed by {expected}")]
+    UnterminatedBlock {
+        statement: &'static str,
+        expected: &'static str,
+        position: Position,
+    },
+
+    #[error("{statement} at {position} has no matching {expected}")]
+    UnmatchedBlockStatement {
+        statement: &'static str,
+        expected: &'static str,
+        position: Position,
+    },
+
+    #[error(
+        "{statement} at {position} can not close {open_statement} at {open_position}, expected {expected}"
+    )]
+    MismatchedBlock {
+        statement: &'static str,
+        position: Position,
+        open_statement: &'static str,
+        open_position: Position,
+        expected: &'static str,
+    },
+
+    #[error("Function {name} expects {expected} argument(s), but {actual} are given at {position}")]
+    WrongNumberOfArguments {
+        name: String,
+        expected: String,
+        actual: usize,
+        position: Position,
+    },
+
+    #[error("Jump to line {line_id}, which does not exist, at {position}")]
+    UndefinedLine { line_id: isize, position: Position },
+
+    #[error("Statement at {position} is shared and can not be resolved")]
+    SharedStatement { position: Position },
+
+    #[error("Line number {line_id} is used twice, at {first} and at {second}")]
+    DuplicateLineNumber {
+        line_id: usize,
+        first: Position,
+        second: Position,
+    },
+
+    #[error(
+        "Line number {line_id} follows the greater line number {previous_line_id} at {position}"
+    )]
+    UnsortedLineNumber {
+        line_id: usize,
+        previous_line_id: usize,
+        position: Position,
+    },
 }
 
 pub type Result<T> = result::Result<T, ParseError>;
@@ -55,20 +128,32 @@ impl<T> Node<T> {
 }
 
 /// Syntax Line:
-/// <line>::= <number> <statement> "\n" | "REM" .* \n
+/// <line>::= <number> <statement> (":" <statement>)* "\n" | "REM" .* \n
 ///
 /// - Comments are already excluded by the tokenizer
+/// - "\n" is the `EndOfLine` token, anything else after the statements is an error
+/// - 'ELSE' is a statement of its own, which needs no ":" before or after it
 #[derive(Serialize, Debug, PartialEq)]
 pub struct Line {
     pub line_id: usize,
-    pub statement: Rc<Node<Statement>>,
+    /// Position of the line number
+    pub position: Position,
+    pub statements: Vec<Rc<Node<Statement>>>,
+}
+
+/// Address of a statement in a program: index of the line in the list of
+/// lines and index of the statement within that line
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StatementIndex {
+    pub line: usize,
+    pub statement: usize,
 }
 
 /// Parse a line from tokens
 impl Line {
     pub fn parse<'a, I>(tokens: &mut Peekable<I>) -> Result<Self>
     where
-        I: Iterator<Item = &'a Token>,
+        I: Iterator<Item = &'a Token> + Clone,
     {
         let line_token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
 
@@ -79,16 +164,83 @@ impl Line {
             });
         };
 
-        let statement = Statement::parse(tokens)?;
+        // - Statements separated by ':' and the end of line,
+        //   a missing end of line is only allowed at the end of input
+        let mut statements = Vec::new();
+        let mut single_line_if = false;
+        loop {
+            let statement = Statement::parse(tokens)?;
+            let is_else = matches!(statement.content, Statement::Else(_));
+            let position = statement.position;
+            if let Statement::If(if_stmt) = &statement.content {
+                single_line_if |= if_stmt.then_statement.is_some();
+            }
+            statements.push(Rc::new(statement));
+
+            match tokens.peek() {
+                // The ELSE of a single line IF needs a statement on the same line
+                None
+                | Some(Token {
+                    kind: TokenType::EndOfLine,
+                    ..
+                }) if is_else && single_line_if => {
+                    return Err(ParseError::MissingStatement {
+                        statement: "ELSE",
+                        position,
+                    });
+                }
+                None
+                | Some(Token {
+                    kind: TokenType::EndOfLine,
+                    ..
+                }) => {
+                    tokens.next();
+                    break;
+                }
+                Some(Token {
+                    kind: TokenType::Colon,
+                    ..
+                }) => {
+                    tokens.next();
+                }
+                // ELSE needs no separator: 'IF <cond> THEN <stmt> ELSE <stmt>'
+                Some(Token {
+                    kind: TokenType::Else,
+                    ..
+                }) => (),
+                Some(_) if is_else => (),
+                Some(token) => {
+                    return Err(ParseError::TrailingToken {
+                        token: format!("{:?}", token.kind),
+                        position: token.position,
+                    });
+                }
+            }
+        }
+
         Ok(Line {
-            statement: Rc::new(statement),
+            statements,
             line_id,
+            position: line_token.position,
         })
     }
 }
 
+/// Options for reading a program
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParseOptions {
+    pub line_order: LineOrder,
+    /// Used where the program is tokenized, `parse_tokens_with` gets tokens
+    pub keyword_mode: KeywordMode,
+}
+
 /// Parse Tokens into an Abstract Syntax Tree (List of Line)
-pub fn parse_tokens<'a>(tokens: &[Token]) -> Result<Vec<Line>> {
+pub fn parse_tokens(tokens: &[Token]) -> Result<Vec<Line>> {
+    parse_tokens_with(tokens, &ParseOptions::default())
+}
+
+/// Parse Tokens into an Abstract Syntax Tree and validate the program structure
+pub fn parse_tokens_with(tokens: &[Token], options: &ParseOptions) -> Result<Vec<Line>> {
     let mut iter_token = tokens.iter().peekable();
 
     let mut lines = Vec::new();
@@ -99,10 +251,15 @@ pub fn parse_tokens<'a>(tokens: &[Token]) -> Result<Vec<Line>> {
         let line = Line::parse(&mut iter_token)?;
         lines.push(line);
     }
-    Ok(lines)
+    validate_lines(lines, options.line_order)
 }
 
 pub fn parse_file(path: impl AsRef<Path>) -> Result<Vec<Line>> {
+    parse_file_with(path, &ParseOptions::default())
+}
+
+/// Same as `parse_file`, with the given options
+pub fn parse_file_with(path: impl AsRef<Path>, options: &ParseOptions) -> Result<Vec<Line>> {
     let path = path.as_ref();
 
     log::info!(r#"Opening file"{path:#?}""#);
@@ -114,10 +271,10 @@ pub fn parse_file(path: impl AsRef<Path>) -> Result<Vec<Line>> {
 
     log::info!(r"Tokenizing");
     let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
-    let tokens = tokenize(&lines)?;
+    let tokens = tokenize_with(&lines, options.keyword_mode)?;
 
     log::info!(r"Parsing");
-    parse_tokens(&tokens)
+    parse_tokens_with(&tokens, options)
 }
 
 // Outsource Unittests to extra file:
@@ -0,0 +1,118 @@
+use super::tokenizer::Position;
+use super::{ParseError, Result};
+
+/// Name and number of arguments of a built-in function, the functions are
+/// implemented by the interpreter
+#[derive(Debug, PartialEq)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub min_args: usize,
+    /// `None` if any number of arguments is allowed
+    pub max_args: Option<usize>,
+}
+
+const fn builtin(name: &'static str, min_args: usize, max_args: Option<usize>) -> Builtin {
+    Builtin {
+        name,
+        min_args,
+        max_args,
+    }
+}
+
+/// All built-in functions, their names can not be used for variables
+pub const BUILTINS: &[Builtin] = &[
+    builtin("ABS", 1, Some(1)),
+    builtin("SGN", 1, Some(1)),
+    builtin("INT", 1, Some(1)),
+    builtin("SQR", 1, Some(1)),
+    builtin("MOD", 2, Some(2)),
+    builtin("MIN", 2, None),
+    builtin("MAX", 2, None),
+    builtin("RND", 0, Some(1)),
+    builtin("LEN", 1, Some(1)),
+    builtin("LEFT$", 2, Some(2)),
+    builtin("RIGHT$", 2, Some(2)),
+    builtin("MID$", 2, Some(3)),
+    builtin("CHR$", 1, Some(1)),
+    builtin("ASC", 1, Some(1)),
+    builtin("STR$", 1, Some(1)),
+    builtin("VAL", 1, Some(1)),
+    builtin("INSTR", 2, Some(3)),
+    builtin("ERR", 0, Some(0)),
+    builtin("ERL", 0, Some(0)),
+];
+
+/// Checks if `name` is the name of a user-defined function, these start with
+/// FN and are defined by DEF
+pub fn is_user_function(name: &str) -> bool {
+    name.get(..2)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("FN"))
+}
+
+/// Finds a built-in function, ignoring the case of the name
+pub fn find_builtin(name: &str) -> Option<&'static Builtin> {
+    BUILTINS
+        .iter()
+        .find(|builtin| builtin.name.eq_ignore_ascii_case(name))
+}
+
+impl Builtin {
+    pub fn check_arity(&self, actual: usize, position: Position) -> Result<()> {
+        let too_many = self.max_args.is_some_and(|max_args| actual > max_args);
+        if actual >= self.min_args && !too_many {
+            return Ok(());
+        }
+
+        let expected = match self.max_args {
+            Some(max_args) if max_args == self.min_args => max_args.to_string(),
+            Some(max_args) => format!("{} to {max_args}", self.min_args),
+            None => format!("at least {}", self.min_args),
+        };
+        Err(ParseError::WrongNumberOfArguments {
+            name: self.name.to_string(),
+            expected,
+            actual,
+            position,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_builtin, is_user_function};
+    use crate::parser::ParseError;
+    use crate::parser::tokenizer::Position;
+
+    #[test]
+    fn test_check_arity() {
+        let position = Position {
+            line_num: 0,
+            col_start: 0,
+            col_end: 3,
+        };
+        let params = [
+            ("abs", 1, None),
+            ("ABS", 2, Some("1")),
+            ("Rnd", 0, None),
+            ("RND", 2, Some("0 to 1")),
+            ("MAX", 5, None),
+            ("MIN", 1, Some("at least 2")),
+            ("mid$", 3, None),
+            ("INSTR", 1, Some("2 to 3")),
+        ];
+
+        for (name, actual, expected) in params {
+            let builtin = find_builtin(name).expect("Builtin exists");
+            let result = builtin.check_arity(actual, position);
+            match (result, expected) {
+                (Ok(()), None) => (),
+                (Err(ParseError::WrongNumberOfArguments { expected, .. }), Some(message)) => {
+                    assert_eq!(expected, message)
+                }
+                (result, _) => panic!("Unexpected result for {name}({actual}): {result:?}"),
+            }
+        }
+        assert!(find_builtin("ABSX").is_none());
+        assert!(is_user_function("fnArea") && !is_user_function("F"));
+    }
+}
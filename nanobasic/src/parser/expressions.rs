@@ -1,15 +1,26 @@
 use super::Node;
+use super::builtins::{Builtin, find_builtin, is_user_function};
 use super::tokenizer::{Position, Token, TokenType};
 use super::{ParseError, Result};
 use serde::Serialize;
 use std::iter::Peekable;
 
-#[derive(Serialize, Debug, PartialEq)]
+#[derive(Serialize, Debug, PartialEq, Clone, Copy)]
 pub enum BinaryOperator {
     Plus,
     Minus,
     Multiply,
     Devide,
+    /// `^`
+    Power,
+    /// `\`, division of integers without remainder
+    IntegerDivide,
+    /// `MOD`, remainder with the sign of the dividend
+    Modulo,
+    /// Bitwise operations on integers
+    And,
+    Or,
+    Xor,
 }
 
 #[derive(Serialize, Debug, PartialEq)]
@@ -24,7 +35,7 @@ pub enum UnaryOperator {
     Minus,
 }
 
-/// Expression: evaluates to a single numericic value (=> NumericExpression in Pyhton code)
+/// Expression: evaluates to a single numeric or string value (=> NumericExpression in Pyhton code)
 #[derive(Serialize, Debug, PartialEq)]
 pub enum Expression {
     /// A numeric expression with two operands like 2 + 2 or 8 / 4
@@ -39,12 +50,120 @@ pub enum Expression {
     /// An integer written out in NanoBASIC code
     NumberLiteral(isize),
 
+    /// A decimal number written out in NanoBASIC code, like 3.14 or 1E-3
+    FloatLiteral(f64),
+
+    /// A string written out in NanoBASIC code, like "Hello"
+    StringLiteral(String),
+
     /// A variable *name* that will have its value retrieved
     VarRetrieve(String),
+
+    /// An array element like A(3) or B(I, J+1) that will have its value retrieved
+    ArrayRetrieve {
+        name: String,
+        indices: Vec<Node<Expression>>,
+    },
+
+    /// Call of a built-in or a user-defined function, `name` is in upper case
+    Call {
+        name: String,
+        args: Vec<Node<Expression>>,
+    },
+}
+
+/// INDICES :=
+/// ( Expression , Expression , ... )
+/// Parses a list of expressions in parentheses, like the indices of an array
+/// element or the arguments of a function
+pub fn parse_indices<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Vec<Node<Expression>>>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let open_token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+    let TokenType::OpenParen = open_token.kind else {
+        return Err(ParseError::WrongToken {
+            expected: "(".to_string(),
+            actual: format!("{:?}", open_token.kind),
+        });
+    };
+
+    parse_list_tail(tokens, open_token)
+}
+
+/// Parses the expressions and the closing parenthesis of a list, the opening
+/// parenthesis `open_token` is already consumed
+fn parse_list_tail<'a, I>(
+    tokens: &mut Peekable<I>,
+    open_token: &Token,
+) -> Result<Node<Vec<Node<Expression>>>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let mut indices = Vec::new();
+    loop {
+        indices.push(parse_expression(tokens)?);
+
+        let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+        match token.kind {
+            TokenType::Comma => continue,
+            TokenType::CloseParen => {
+                let position = Position {
+                    col_end: token.position.col_end,
+                    ..open_token.position
+                };
+                return Ok(Node {
+                    content: indices,
+                    position,
+                });
+            }
+            _ => {
+                return Err(ParseError::WrongToken {
+                    expected: ", or )".to_string(),
+                    actual: format!("{:?}", token.kind),
+                });
+            }
+        }
+    }
+}
+
+/// Parses the arguments of a function call, the parentheses are optional
+/// without arguments. The number of arguments of a built-in function is
+/// checked here, the one of a user-defined function when it is called.
+fn parse_call<'a, I>(
+    tokens: &mut Peekable<I>,
+    name: &str,
+    builtin: Option<&Builtin>,
+    name_token: &Token,
+) -> Result<Node<Expression>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let mut position = name_token.position;
+    let mut args = Vec::new();
+    if let Some(open_token) = tokens.next_if(|token| token.kind == TokenType::OpenParen) {
+        if let Some(close_token) = tokens.next_if(|token| token.kind == TokenType::CloseParen) {
+            // Empty parentheses
+            position.col_end = close_token.position.col_end;
+        } else {
+            let list = parse_list_tail(tokens, open_token)?;
+            position.col_end = list.position.col_end;
+            args = list.content;
+        }
+    }
+
+    if let Some(builtin) = builtin {
+        builtin.check_arity(args.len(), position)?;
+    }
+    let content = Expression::Call {
+        name: name.to_uppercase(),
+        args,
+    };
+    Ok(Node { content, position })
 }
 
 /// FACTOR :=
-/// Variable | Number | (Expression) | -FACTOR
+/// Variable | Variable INDICES | Function | Number | String | (Expression)
 pub fn parse_factor<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Expression>>
 where
     I: Iterator<Item = &'a Token>,
@@ -54,8 +173,31 @@ where
     let token = first_token;
     let this_node: Node<Expression> = match &token.kind {
         TokenType::Variable(var) => {
-            let content = Expression::VarRetrieve(var.clone());
-            Node::new(first_token, content)
+            if let Some(builtin) = find_builtin(var) {
+                parse_call(tokens, var, Some(builtin), first_token)?
+            } else if is_user_function(var) {
+                parse_call(tokens, var, None, first_token)?
+            } else if let Some(Token {
+                kind: TokenType::OpenParen,
+                ..
+            }) = tokens.peek()
+            {
+                let indices = parse_indices(tokens)?;
+                let content = Expression::ArrayRetrieve {
+                    name: var.clone(),
+                    indices: indices.content,
+                };
+                Node {
+                    content,
+                    position: Position {
+                        col_end: indices.position.col_end,
+                        ..first_token.position
+                    },
+                }
+            } else {
+                let content = Expression::VarRetrieve(var.clone());
+                Node::new(first_token, content)
+            }
         }
 
         TokenType::Number(num) => {
@@ -63,6 +205,16 @@ where
             Node::new(first_token, content)
         }
 
+        TokenType::Float(num) => {
+            let content = Expression::FloatLiteral(*num);
+            Node::new(first_token, content)
+        }
+
+        TokenType::String(text) => {
+            let content = Expression::StringLiteral(text.clone());
+            Node::new(first_token, content)
+        }
+
         TokenType::OpenParen => {
             let inner_node: Node<Expression> = parse_expression(tokens)?;
 
@@ -84,25 +236,11 @@ where
             }
         }
 
-        TokenType::Minus => {
-            let factor = parse_factor(tokens)?;
-            let col_end = factor.position.col_end;
-            let content = Expression::UnaryOperation {
-                expression: Box::new(factor),
-                operator: UnaryOperator::Minus,
-            };
-
-            Node {
-                content,
-                position: Position {
-                    col_end,
-                    ..token.position
-                },
-            }
-        }
+        // MOD can also be called like a function, MOD(7, 3) is 7 MOD 3
+        TokenType::Mod => parse_call(tokens, "MOD", find_builtin("MOD"), first_token)?,
         _ => {
             return Err(ParseError::WrongToken {
-                expected: "Numeric expression".to_string(),
+                expected: "Expression".to_string(),
                 actual: format!("{:?}", token.kind),
             });
         }
@@ -111,109 +249,141 @@ where
     Ok(this_node)
 }
 
-/// Term :=
-/// FACTOR *|/ FACTOR *|/ FACTOR ...
-pub fn parse_term<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Expression>>
+/// Precedence of the binary operators, a higher level binds tighter:
+///
+/// | Level | Operators       | Associativity |
+/// |-------|-----------------|---------------|
+/// | 9     | `^`             | right         |
+/// | 8     | unary `-`       |               |
+/// | 7     | `*` `/`         | left          |
+/// | 6     | `\`             | left          |
+/// | 5     | `MOD`           | left          |
+/// | 4     | `+` `-`         | left          |
+/// | 3     | `AND`           | left          |
+/// | 2     | `OR`            | left          |
+/// | 1     | `XOR`           | left          |
+///
+/// In conditions the operands of comparisons stop at level 4, there AND and
+/// OR combine the comparisons logically.
+fn binary_operator(kind: &TokenType) -> Option<(BinaryOperator, u8)> {
+    let operator = match kind {
+        TokenType::Power => (BinaryOperator::Power, POWER_LEVEL),
+        TokenType::Multiply => (BinaryOperator::Multiply, 7),
+        TokenType::Divide => (BinaryOperator::Devide, 7),
+        TokenType::IntegerDivide => (BinaryOperator::IntegerDivide, 6),
+        TokenType::Mod => (BinaryOperator::Modulo, 5),
+        TokenType::Plus => (BinaryOperator::Plus, ADDITIVE_LEVEL),
+        TokenType::Minus => (BinaryOperator::Minus, ADDITIVE_LEVEL),
+        TokenType::And => (BinaryOperator::And, 3),
+        TokenType::Or => (BinaryOperator::Or, 2),
+        TokenType::Xor => (BinaryOperator::Xor, 1),
+        _ => return None,
+    };
+    Some(operator)
+}
+
+const POWER_LEVEL: u8 = 9;
+const UNARY_MINUS_LEVEL: u8 = 8;
+const MULTIPLICATIVE_LEVEL: u8 = 7;
+const ADDITIVE_LEVEL: u8 = 4;
+const LOWEST_LEVEL: u8 = 1;
+
+/// Parses operands and binary operators of at least the precedence
+/// `min_level` (precedence climbing)
+fn parse_binary_operation<'a, I>(
+    tokens: &mut Peekable<I>,
+    min_level: u8,
+) -> Result<Node<Expression>>
 where
     I: Iterator<Item = &'a Token>,
 {
-    let mut left_node = parse_factor(tokens)?;
-    loop {
-        let Some(&token) = tokens.peek() else {
-            return Ok(left_node);
-        };
+    let mut left = parse_unary(tokens)?;
+    while let Some((operator, level)) = tokens
+        .peek()
+        .and_then(|token| binary_operator(&token.kind))
+        .filter(|(_, level)| *level >= min_level)
+    {
+        let token = tokens.next().expect("Operator was peeked");
 
-        left_node = match &token.kind {
-            t @ (TokenType::Multiply | TokenType::Divide) => {
-                _ = tokens.next().expect("Unexpected Error");
+        // The right operand of a left-associative operator only takes tighter operators
+        let right_level = if operator == BinaryOperator::Power {
+            level
+        } else {
+            level + 1
+        };
+        let right = parse_binary_operation(tokens, right_level)?;
 
-                let col_start = left_node.position.col_start;
-                let operator = if *t == TokenType::Multiply {
-                    BinaryOperator::Multiply
-                } else {
-                    BinaryOperator::Devide
-                };
+        let position = Position {
+            line_num: token.position.line_num,
+            col_start: left.position.col_start,
+            col_end: right.position.col_end,
+        };
+        let binary_operation = BinaryOperation {
+            left,
+            right,
+            operator,
+        };
+        let content = Expression::BinaryOperation(Box::new(binary_operation));
+        left = Node { content, position };
+    }
+    Ok(left)
+}
 
-                let right_node = parse_factor(tokens)?;
-                let col_end = right_node.position.col_end;
+/// UNARY :=
+/// -UNARY | FACTOR, the operand of the minus may contain `^`, so -2^2 is -4
+fn parse_unary<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Expression>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let Some(token) = tokens.next_if(|token| token.kind == TokenType::Minus) else {
+        return parse_factor(tokens);
+    };
 
-                let binary_operation = BinaryOperation {
-                    left: left_node,
-                    right: right_node,
-                    operator,
-                };
-                let content = Expression::BinaryOperation(Box::new(binary_operation));
+    let operand = parse_binary_operation(tokens, UNARY_MINUS_LEVEL + 1)?;
+    let position = Position {
+        col_end: operand.position.col_end,
+        ..token.position
+    };
+    let content = Expression::UnaryOperation {
+        expression: Box::new(operand),
+        operator: UnaryOperator::Minus,
+    };
+    Ok(Node { content, position })
+}
 
-                let new_node = Node {
-                    content,
-                    position: Position {
-                        col_start,
-                        col_end,
-                        line_num: token.position.line_num,
-                    },
-                };
-                new_node
-            }
-            _ => {
-                let ret = Ok(left_node);
-                return ret;
-            }
-        }
-    }
+/// Term :=
+/// UNARY (*|/ UNARY)* ...
+pub fn parse_term<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Expression>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    parse_binary_operation(tokens, MULTIPLICATIVE_LEVEL)
 }
 
-/// Expression :=
-/// TERM +|- TERM +|- TERM ...
-/// a term in this context is a chain of one or more factors
+/// Expression with all binary operators, see `binary_operator` for their
+/// precedence
 pub fn parse_expression<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Expression>>
 where
     I: Iterator<Item = &'a Token>,
 {
-    let mut left = parse_term(tokens)?;
-    loop {
-        let Some(&token) = tokens.peek() else {
-            return Ok(left);
-        };
-
-        left = match &token.kind {
-            t @ (TokenType::Plus | TokenType::Minus) => {
-                _ = tokens.next().expect("Unexpected Error");
-                let col_start = left.position.col_start;
-                let operator = if *t == TokenType::Plus {
-                    BinaryOperator::Plus
-                } else {
-                    BinaryOperator::Minus
-                };
+    parse_binary_operation(tokens, LOWEST_LEVEL)
+}
 
-                let right = parse_term(tokens)?;
-                let col_end = right.position.col_end;
-                let binary_op = BinaryOperation {
-                    left,
-                    right,
-                    operator,
-                };
-                let content = Expression::BinaryOperation(Box::new(binary_op));
-                Node {
-                    content,
-                    position: Position {
-                        line_num: token.position.line_num,
-                        col_start,
-                        col_end,
-                    },
-                }
-            }
-            _ => {
-                let ret = Ok(left);
-                return ret;
-            }
-        }
-    }
+/// Expression without the bitwise operators AND, OR and XOR. Used for the
+/// operands of comparisons, where AND and OR are logical operators.
+pub fn parse_arithmetic_expression<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Expression>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    parse_binary_operation(tokens, ADDITIVE_LEVEL)
 }
 
 #[cfg(test)]
 mod tests {
     use super::Result;
-    use crate::parser::expressions::{parse_expression, parse_term};
+    use crate::parser::expressions::{
+        BinaryOperator, Expression, Node, UnaryOperator, parse_expression, parse_term,
+    };
     use crate::parser::tokenizer::{Token, tokenize};
 
     #[test]
@@ -238,7 +408,91 @@ mod tests {
 
         let x = parse_expression(&mut iter_tokens)?;
         println!("{:#?}", x);
-        assert!(true);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_parse_call() -> Result<()> {
+        let lines = vec!["max(1, rnd, Rnd()) + 2".to_string()];
+        let tokens = tokenize(&lines)?;
+        let mut iter_tokens = tokens.iter().peekable();
+
+        let expression = parse_expression(&mut iter_tokens)?;
+        let Expression::BinaryOperation(operation) = expression.content else {
+            panic!("Expected a binary operation: {expression:?}");
+        };
+        let Expression::Call { name, args } = &operation.left.content else {
+            panic!("Expected a call: {:?}", operation.left);
+        };
+        assert_eq!(name, "MAX");
+        assert_eq!(operation.left.position.col_end, 18);
+        assert_eq!(args.len(), 3);
+        for arg in &args[1..] {
+            assert!(
+                matches!(&arg.content, Expression::Call { name, args } if name == "RND" && args.is_empty())
+            );
+        }
+        Ok(())
+    }
+
+    /// Writes the tree with parentheses around every operation
+    fn bracketed(node: &Node<Expression>) -> String {
+        match &node.content {
+            Expression::BinaryOperation(operation) => {
+                let operator = match operation.operator {
+                    BinaryOperator::Plus => "+",
+                    BinaryOperator::Minus => "-",
+                    BinaryOperator::Multiply => "*",
+                    BinaryOperator::Devide => "/",
+                    BinaryOperator::Power => "^",
+                    BinaryOperator::IntegerDivide => "\\",
+                    BinaryOperator::Modulo => "MOD",
+                    BinaryOperator::And => "AND",
+                    BinaryOperator::Or => "OR",
+                    BinaryOperator::Xor => "XOR",
+                };
+                let left = bracketed(&operation.left);
+                let right = bracketed(&operation.right);
+                format!("({left} {operator} {right})")
+            }
+            Expression::UnaryOperation {
+                expression,
+                operator: UnaryOperator::Minus,
+            } => format!("(-{})", bracketed(expression)),
+            Expression::NumberLiteral(n) => n.to_string(),
+            Expression::VarRetrieve(name) => name.clone(),
+            other => panic!("Unexpected expression: {other:?}"),
+        }
+    }
+
+    #[test]
+    pub fn test_precedence() -> Result<()> {
+        let cases = [
+            ("1 + 2 * 3", "(1 + (2 * 3))"),
+            ("1 - 2 - 3", "((1 - 2) - 3)"),
+            ("2 ^ 3 ^ 2", "(2 ^ (3 ^ 2))"),
+            ("-2 ^ 2", "(-(2 ^ 2))"),
+            ("2 ^ -1", "(2 ^ (-1))"),
+            ("-A * B", "((-A) * B)"),
+            ("A * B ^ 2", "(A * (B ^ 2))"),
+            ("7 \\ 2 * 3", "(7 \\ (2 * 3))"),
+            ("7 MOD 4 \\ 2", "(7 MOD (4 \\ 2))"),
+            ("1 + 7 MOD 4", "(1 + (7 MOD 4))"),
+            ("A AND B + 1", "(A AND (B + 1))"),
+            ("A OR B AND C", "(A OR (B AND C))"),
+            ("A XOR B OR C", "(A XOR (B OR C))"),
+            ("(1 + 2) * 3", "((1 + 2) * 3)"),
+        ];
+        for (line, expected) in cases {
+            let tokens = tokenize(&[line])?;
+            let expression = parse_expression(&mut tokens.iter().peekable())?;
+            assert_eq!(bracketed(&expression), expected, "{line}");
+        }
+
+        let tokens = tokenize(&["2 ^ 3 ^ 2"])?;
+        let expression = parse_expression(&mut tokens.iter().peekable())?;
+        assert_eq!(expression.position.col_start, 0);
+        assert_eq!(expression.position.col_end, 9);
         Ok(())
     }
 }
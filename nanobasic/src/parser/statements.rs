@@ -1,44 +1,128 @@
+pub mod data_statement;
+pub mod def_statement;
+pub mod dim_statement;
+pub mod for_statement;
 pub mod if_statement;
+pub mod input_statement;
 pub mod let_statment;
+pub mod loop_statement;
+pub mod on_statement;
 pub mod print_statment;
+pub mod resume_statement;
 use super::Node;
+use super::StatementIndex;
 use super::expressions::Expression;
 use super::expressions::parse_expression;
 use super::tokenizer::Position;
 use super::tokenizer::Token;
 use super::tokenizer::TokenType;
 use super::{ParseError, Result};
-use if_statement::IfStatement;
-use let_statment::LetStatement;
+use data_statement::{DataStatement, parse_data};
+use def_statement::FunctionDefinition;
+use dim_statement::{ArrayDeclarations, parse_declarations};
+use for_statement::ForStatement;
+use if_statement::{ElseIfStatement, IfStatement};
+use input_statement::InputStatement;
+use let_statment::{LetStatement, LetTarget, parse_targets};
+use loop_statement::{DoStatement, LoopStatement, WhileStatement};
+use on_statement::OnStatement;
 use print_statment::{Printables, parse_printables};
+use resume_statement::ResumeTarget;
 use serde::Serialize;
 use std::iter::Peekable;
+use std::rc::Rc;
 
 /// <statement> ::=
 ///    'PRINT' <expr-list>
-///  | 'IF'    <boolean-expr> 'THEN' <statement>
+///  | 'IF'    <boolean-expr> 'THEN' <statement>?
+///  | 'ELSEIF' <boolean-expr> 'THEN'
+///  | 'ELSE'
+///  | 'END' 'IF'
+///  | 'WHILE' <boolean-expr>
+///  | 'WEND'
+///  | 'DO'    (('WHILE' | 'UNTIL') <boolean-expr>)?
+///  | 'LOOP'  (('WHILE' | 'UNTIL') <boolean-expr>)?
 ///  | 'GOTO'  <expression>
 ///  | 'LET'   <var> = <expression>
 ///  | 'GOSUB' <expression>
+///  | 'ON'    <expression> ('GOTO' | 'GOSUB') <expression> (',' <expression>)*
+///  | 'ON' 'ERROR' 'GOTO' <expression>
+///  | 'RESUME' ('NEXT' | <expression>)?
 ///  | 'RETURN'
+///  | 'END'
+///  | 'STOP'
+///  | 'RANDOMIZE' <expression>
+///  | 'DEF'   <fn-name> ('(' <var> (',' <var>)* ')')? '=' <expression>
+///  | 'FOR'   <var> = <expression> 'TO' <expression> ('STEP' <expression>)?
+///  | 'NEXT'  <var>?
+///  | 'DIM'   <array> (',' <array>)*
+///  | 'INPUT' (<string> ';')? <target> (',' <target>)*
+///  | 'DATA'  <constant> (',' <constant>)*
+///  | 'READ'  <target> (',' <target>)*
+///  | 'RESTORE' <expression>?
 ///
 #[derive(Serialize, Debug, PartialEq)]
 pub enum Statement {
     Print(Box<Printables>),
     If(Box<IfStatement>),
-    GoSub(Box<Expression>),
-    GoTo(Box<Expression>),
+    GoSub(Box<Node<Expression>>),
+    GoTo(Box<Node<Expression>>),
+    On(Box<OnStatement>),
+    /// Sets the line which handles errors, 0 lets errors abort the program
+    OnError(Box<Node<Expression>>),
+    Resume(ResumeTarget),
     Let(Box<LetStatement>),
     Return,
+    /// Ends the program
+    End,
+    /// Halts the program, it can be continued after the STOP
+    Stop,
+    /// Seeds the generator of RND
+    Randomize(Box<Node<Expression>>),
+    /// Defines a function, shared with the interpreter's function table
+    Def(Rc<FunctionDefinition>),
+    For(Box<ForStatement>),
+    Next(Option<String>),
+    Dim(Box<ArrayDeclarations>),
+    Input(Box<InputStatement>),
+    /// Values for READ, they are collected before the program runs
+    Data(Box<DataStatement>),
+    Read(Box<Vec<Node<LetTarget>>>),
+    /// Resets the data pointer to the first DATA value, or to the first one
+    /// at or after the given line
+    Restore(Option<Box<Node<Expression>>>),
+    ElseIf(Box<ElseIfStatement>),
+    /// Start of an ELSE branch, the target is the END IF of a block IF.
+    /// Without a target (single line ELSE) the rest of the line is skipped.
+    Else(Option<StatementIndex>),
+    EndIf,
+    While(Box<WhileStatement>),
+    /// End of a WHILE loop, the target is the matching WHILE
+    Wend(Option<StatementIndex>),
+    Do(Box<DoStatement>),
+    Loop(Box<LoopStatement>),
 }
 
 use Statement::*;
 
 impl Statement {
+    /// The statement itself followed by the statements after THEN of
+    /// single line IFs, for `IF A THEN IF B THEN PRINT` these are both IFs
+    /// and the PRINT
+    pub fn with_then_statements(&self) -> impl Iterator<Item = &Statement> {
+        std::iter::successors(Some(self), |statement| match statement {
+            If(if_statement) => if_statement
+                .then_statement
+                .as_ref()
+                .map(|then_statement| &then_statement.content),
+            _ => None,
+        })
+    }
+
     /// Parse statement from tokens
     pub fn parse<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Self>>
     where
-        I: Iterator<Item = &'a Token>,
+        I: Iterator<Item = &'a Token> + Clone,
     {
         use TokenType as TT;
         let token: &Token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
@@ -54,25 +138,158 @@ impl Statement {
                 let content = If(Box::new(content));
                 wrap_statement_in_node(content, token, position)
             }
+            TT::ElseIf => {
+                let Node { content, position } = ElseIfStatement::parse_node(tokens)?;
+                let content = ElseIf(Box::new(content));
+                wrap_statement_in_node(content, token, position)
+            }
+            TT::Else => Node {
+                position: token.position,
+                content: Else(None),
+            },
+            TT::End => match tokens.next_if(|token| token.kind == TT::If) {
+                Some(if_token) => wrap_statement_in_node(EndIf, token, if_token.position),
+                None => Node {
+                    position: token.position,
+                    content: End,
+                },
+            },
+            TT::Stop => Node {
+                position: token.position,
+                content: Stop,
+            },
+            TT::Randomize => {
+                let expression = parse_expression(tokens)?;
+                let position = expression.position;
+                let content = Randomize(Box::new(expression));
+                wrap_statement_in_node(content, token, position)
+            }
+            TT::Def => {
+                let Node { content, position } = FunctionDefinition::parse(tokens)?;
+                let content = Def(Rc::new(content));
+                wrap_statement_in_node(content, token, position)
+            }
+            TT::While => {
+                let Node { content, position } = WhileStatement::parse(tokens)?;
+                let content = While(Box::new(content));
+                wrap_statement_in_node(content, token, position)
+            }
+            TT::Wend => Node {
+                position: token.position,
+                content: Wend(None),
+            },
+            TT::Do => {
+                let Node { content, position } = DoStatement::parse(tokens, token.position)?;
+                let content = Do(Box::new(content));
+                wrap_statement_in_node(content, token, position)
+            }
+            TT::Loop => {
+                let Node { content, position } = LoopStatement::parse(tokens, token.position)?;
+                let content = Loop(Box::new(content));
+                wrap_statement_in_node(content, token, position)
+            }
             TT::Let => {
                 let Node { content, position } = LetStatement::parse(tokens)?;
                 let content = Let(Box::new(content));
                 wrap_statement_in_node(content, token, position)
             }
             TT::Goto => {
-                let Node { content, position } = parse_expression(tokens)?;
-                let content = GoTo(Box::new(content));
+                let expression = parse_expression(tokens)?;
+                let position = expression.position;
+                let content = GoTo(Box::new(expression));
                 wrap_statement_in_node(content, token, position)
             }
             TT::Gosub => {
-                let Node { content, position } = parse_expression(tokens)?;
-                let content = GoSub(Box::new(content));
+                let expression = parse_expression(tokens)?;
+                let position = expression.position;
+                let content = GoSub(Box::new(expression));
                 wrap_statement_in_node(content, token, position)
             }
+            TT::On => match tokens.next_if(|token| token.kind == TT::Error) {
+                Some(_) => {
+                    let goto_token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+                    if goto_token.kind != TT::Goto {
+                        return Err(ParseError::WrongToken {
+                            expected: "GOTO".to_string(),
+                            actual: format!("{:?}", goto_token.kind),
+                        });
+                    }
+                    let expression = parse_expression(tokens)?;
+                    let position = expression.position;
+                    let content = OnError(Box::new(expression));
+                    wrap_statement_in_node(content, token, position)
+                }
+                None => {
+                    let Node { content, position } = OnStatement::parse(tokens)?;
+                    let content = On(Box::new(content));
+                    wrap_statement_in_node(content, token, position)
+                }
+            },
+            TT::Resume => {
+                let Node { content, position } = ResumeTarget::parse(tokens, token.position)?;
+                Node {
+                    content: Resume(content),
+                    position,
+                }
+            }
             TT::Return => Node {
                 position: token.position,
                 content: Return,
             },
+            TT::For => {
+                let Node { content, position } = ForStatement::parse(tokens)?;
+                let content = For(Box::new(content));
+                wrap_statement_in_node(content, token, position)
+            }
+            TT::Dim => {
+                let Node { content, position } = parse_declarations(tokens)?;
+                let content = Dim(Box::new(content));
+                wrap_statement_in_node(content, token, position)
+            }
+            TT::Input => {
+                let Node { content, position } = InputStatement::parse(tokens)?;
+                let content = Input(Box::new(content));
+                wrap_statement_in_node(content, token, position)
+            }
+            TT::Data => {
+                let Node { content, position } = parse_data(tokens)?;
+                let content = Data(Box::new(content));
+                wrap_statement_in_node(content, token, position)
+            }
+            TT::Read => {
+                let Node { content, position } = parse_targets(tokens)?;
+                let content = Read(Box::new(content));
+                wrap_statement_in_node(content, token, position)
+            }
+            TT::Restore => match tokens.peek() {
+                None
+                | Some(Token {
+                    kind: TT::EndOfLine | TT::Colon | TT::Else,
+                    ..
+                }) => Node {
+                    position: token.position,
+                    content: Restore(None),
+                },
+                Some(_) => {
+                    let expression = parse_expression(tokens)?;
+                    let position = expression.position;
+                    let content = Restore(Some(Box::new(expression)));
+                    wrap_statement_in_node(content, token, position)
+                }
+            },
+            TT::Next => match tokens.peek() {
+                Some(Token {
+                    kind: TokenType::Variable(var),
+                    position,
+                }) => {
+                    tokens.next();
+                    wrap_statement_in_node(Next(Some(var.clone())), token, *position)
+                }
+                _ => Node {
+                    position: token.position,
+                    content: Next(None),
+                },
+            },
             _ => {
                 return Err(ParseError::WrongToken {
                     expected: "Statement".to_string(),
@@ -91,3 +308,22 @@ fn wrap_statement_in_node(content: Statement, token: &Token, postion: Position)
     };
     Node { content, position }
 }
+
+/// Consumes the next token, which has to be of the given kind
+pub(super) fn expect_token<'a, I>(
+    tokens: &mut Peekable<I>,
+    kind: TokenType,
+    expected: &str,
+) -> Result<()>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+    if token.kind != kind {
+        return Err(ParseError::WrongToken {
+            expected: expected.to_string(),
+            actual: format!("{:?}", token.kind),
+        });
+    }
+    Ok(())
+}
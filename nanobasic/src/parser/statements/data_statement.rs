@@ -0,0 +1,105 @@
+use super::Node;
+use super::{ParseError, Result};
+use crate::parser::tokenizer::{Position, Token, TokenType};
+use serde::Serialize;
+use std::iter::Peekable;
+
+/// Value written out in a DATA statement
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub enum Constant {
+    Integer(isize),
+    Float(f64),
+    String(String),
+}
+
+/// 'DATA' <constant> (',' <constant>)*
+pub type DataStatement = Vec<Node<Constant>>;
+
+/// <constant> ::= '-'? <number> | <string>
+fn parse_constant<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Constant>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+    let minus = token.kind == TokenType::Minus;
+    let number_token = if minus {
+        tokens.next().ok_or(ParseError::UnexpectedEOF)?
+    } else {
+        token
+    };
+
+    let content = match (&number_token.kind, minus) {
+        (TokenType::Number(n), false) => Constant::Integer(*n as isize),
+        (TokenType::Number(n), true) => Constant::Integer(-(*n as isize)),
+        (TokenType::Float(f), false) => Constant::Float(*f),
+        (TokenType::Float(f), true) => Constant::Float(-f),
+        (TokenType::String(s), false) => Constant::String(s.clone()),
+        (kind, _) => {
+            return Err(ParseError::WrongToken {
+                expected: "Number or string".to_string(),
+                actual: format!("{kind:?}"),
+            });
+        }
+    };
+    let position = Position {
+        col_end: number_token.position.col_end,
+        ..token.position
+    };
+    Ok(Node { content, position })
+}
+
+pub fn parse_data<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<DataStatement>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let first = parse_constant(tokens)?;
+    let mut position = first.position;
+    let mut constants = vec![first];
+    while tokens
+        .next_if(|token| token.kind == TokenType::Comma)
+        .is_some()
+    {
+        let constant = parse_constant(tokens)?;
+        position.col_end = constant.position.col_end;
+        constants.push(constant);
+    }
+
+    Ok(Node {
+        content: constants,
+        position,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Constant, parse_data};
+    use crate::parser::Result;
+    use crate::parser::tokenizer::tokenize;
+
+    #[test]
+    fn test_parse_data() -> Result<()> {
+        let tokens = tokenize(&[r#"1, -2.5, - 3, "Hello, World""#])?;
+        let data = parse_data(&mut tokens.iter().peekable())?;
+        let values = data
+            .content
+            .iter()
+            .map(|node| &node.content)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            [
+                &Constant::Integer(1),
+                &Constant::Float(-2.5),
+                &Constant::Integer(-3),
+                &Constant::String("Hello, World".to_string())
+            ]
+        );
+        assert_eq!(data.content[2].position.col_start, 9);
+
+        for line in ["1,", "A", "-\"A\""] {
+            let tokens = tokenize(&[line])?;
+            assert!(parse_data(&mut tokens.iter().peekable()).is_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use super::Node;
+use super::expect_token;
+use super::{ParseError, Result};
+use crate::parser::builtins::is_user_function;
+use crate::parser::expressions::{Expression, parse_expression};
+use crate::parser::tokenizer::{Position, Token, TokenType};
+use serde::Serialize;
+use std::iter::Peekable;
+
+/// 'DEF' <fn-name> ('(' (<var> (',' <var>)*)? ')')? '=' <expression>
+///
+/// A user-defined function, its name starts with FN and is stored in upper case
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FunctionDefinition {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Node<Expression>,
+}
+
+fn parse_params<'a, I>(tokens: &mut Peekable<I>) -> Result<Vec<String>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let mut params = Vec::new();
+    if tokens
+        .next_if(|token| token.kind == TokenType::OpenParen)
+        .is_none()
+    {
+        return Ok(params);
+    }
+    if tokens
+        .next_if(|token| token.kind == TokenType::CloseParen)
+        .is_some()
+    {
+        return Ok(params);
+    }
+
+    loop {
+        let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+        match &token.kind {
+            TokenType::Variable(name) if !params.contains(name) => params.push(name.clone()),
+            kind => {
+                return Err(ParseError::WrongToken {
+                    expected: "Parameter".to_string(),
+                    actual: format!("{kind:?}"),
+                });
+            }
+        }
+
+        let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+        match token.kind {
+            TokenType::Comma => continue,
+            TokenType::CloseParen => return Ok(params),
+            _ => {
+                return Err(ParseError::WrongToken {
+                    expected: ", or )".to_string(),
+                    actual: format!("{:?}", token.kind),
+                });
+            }
+        }
+    }
+}
+
+impl FunctionDefinition {
+    pub fn parse<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Self>>
+    where
+        I: Iterator<Item = &'a Token>,
+    {
+        let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+        let name = match &token.kind {
+            TokenType::Variable(name) if is_user_function(name) => name.to_uppercase(),
+            kind => {
+                return Err(ParseError::WrongToken {
+                    expected: "Function name starting with FN".to_string(),
+                    actual: format!("{kind:?}"),
+                });
+            }
+        };
+
+        let params = parse_params(tokens)?;
+        expect_token(tokens, TokenType::Equal, "=")?;
+        let body = parse_expression(tokens)?;
+
+        let position = Position {
+            col_end: body.position.col_end,
+            ..token.position
+        };
+        let content = FunctionDefinition { name, params, body };
+        Ok(Node { content, position })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FunctionDefinition;
+    use crate::parser::Result;
+    use crate::parser::tokenizer::tokenize;
+
+    #[test]
+    fn test_function_definition() -> Result<()> {
+        let lines = ["fnArea(W, H) = W * H"];
+        let tokens = tokenize(&lines)?;
+        let definition = FunctionDefinition::parse(&mut tokens.iter().peekable())?;
+        assert_eq!(definition.content.name, "FNAREA");
+        assert_eq!(definition.content.params, ["W", "H"]);
+        assert_eq!(definition.position.col_end, 20);
+
+        for line in ["FNA(X, X) = X", "A(X) = X", "FNA(X = X", "FNA(1) = 1"] {
+            let tokens = tokenize(&[line])?;
+            assert!(FunctionDefinition::parse(&mut tokens.iter().peekable()).is_err());
+        }
+        Ok(())
+    }
+}
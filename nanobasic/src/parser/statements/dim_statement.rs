@@ -0,0 +1,87 @@
+use super::Node;
+use super::{ParseError, Result};
+use crate::parser::expressions::{Expression, parse_indices};
+use crate::parser::tokenizer::{Position, Token, TokenType};
+use serde::Serialize;
+use std::iter::Peekable;
+
+/// <array> '(' <expression> (',' <expression>)* ')'
+///
+/// Every expression gives the highest valid index of one dimension
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ArrayDeclaration {
+    pub name: String,
+    pub dimensions: Vec<Node<Expression>>,
+}
+
+pub type ArrayDeclarations = Vec<Node<ArrayDeclaration>>;
+
+fn parse_one_declaration<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<ArrayDeclaration>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+    let TokenType::Variable(name) = &token.kind else {
+        return Err(ParseError::WrongToken {
+            expected: "Array name".to_string(),
+            actual: format!("{:?}", token.kind),
+        });
+    };
+
+    let dimensions = parse_indices(tokens)?;
+    let position = Position {
+        col_end: dimensions.position.col_end,
+        ..token.position
+    };
+    let content = ArrayDeclaration {
+        name: name.clone(),
+        dimensions: dimensions.content,
+    };
+    Ok(Node { content, position })
+}
+
+/// 'DIM' <array> (',' <array>)*
+pub fn parse_declarations<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<ArrayDeclarations>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let mut declarations = Vec::new();
+    loop {
+        declarations.push(parse_one_declaration(tokens)?);
+
+        if let Some(Token {
+            kind: TokenType::Comma,
+            ..
+        }) = tokens.peek()
+        {
+            tokens.next();
+        } else {
+            let mut position = declarations[0].position;
+            position.col_end = declarations.last().unwrap().position.col_end;
+            return Ok(Node {
+                content: declarations,
+                position,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Result;
+    use crate::parser::tokenizer::tokenize;
+
+    #[test]
+    fn test_parse_declarations() -> Result<()> {
+        let lines = vec!["A(10), B(3, 4)".to_string()];
+        let tokens = tokenize(&lines)?;
+
+        let mut iter_token = tokens.iter().peekable();
+        let result = super::parse_declarations(&mut iter_token)?;
+        println!("{:#?}", result);
+
+        assert_eq!(result.content.len(), 2);
+        assert_eq!(result.content[1].content.dimensions.len(), 2);
+        Ok(())
+    }
+}
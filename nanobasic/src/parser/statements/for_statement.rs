@@ -0,0 +1,91 @@
+use super::Node;
+use super::expect_token;
+use super::{ParseError, Result};
+use crate::parser::expressions::{Expression, parse_expression};
+use crate::parser::tokenizer::{Token, TokenType};
+use serde::Serialize;
+use std::iter::Peekable;
+
+/// 'FOR' <var> = <expression> 'TO' <expression> ('STEP' <expression>)?
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ForStatement {
+    pub variable: String,
+    pub start: Node<Expression>,
+    pub end: Node<Expression>,
+    pub step: Option<Node<Expression>>,
+}
+
+impl ForStatement {
+    pub fn parse<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Self>>
+    where
+        I: Iterator<Item = &'a Token>,
+    {
+        // - Loop variable
+        let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+        let mut position = token.position;
+
+        let TokenType::Variable(variable) = &token.kind else {
+            return Err(ParseError::WrongToken {
+                expected: "Variable".to_string(),
+                actual: format!("{:?}", token.kind),
+            });
+        };
+
+        expect_token(tokens, TokenType::Equal, "=")?;
+        let start = parse_expression(tokens)?;
+
+        expect_token(tokens, TokenType::To, "TO")?;
+        let end = parse_expression(tokens)?;
+        position.col_end = end.position.col_end;
+
+        let step = if let Some(Token {
+            kind: TokenType::Step,
+            ..
+        }) = tokens.peek()
+        {
+            tokens.next();
+            let step = parse_expression(tokens)?;
+            position.col_end = step.position.col_end;
+            Some(step)
+        } else {
+            None
+        };
+
+        let content = ForStatement {
+            variable: variable.clone(),
+            start,
+            end,
+            step,
+        };
+        Ok(Node { content, position })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ForStatement;
+    use crate::parser::Result;
+    use crate::parser::tokenizer::tokenize;
+
+    #[test]
+    fn test_for_statement() -> Result<()> {
+        let lines = [
+            vec!["I = 1 TO 10".to_string()],
+            vec!["I = 10 TO 1 STEP -2".to_string()],
+        ];
+
+        for line in &lines {
+            println!("{:#?}", line);
+
+            println!("* Tokenizing");
+            let tokens = tokenize(line)?;
+
+            println!("* Parsing");
+            let mut iter_token = tokens.iter().peekable();
+            let result = ForStatement::parse(&mut iter_token)?;
+            println!("{result:#?}");
+            assert_eq!(result.content.variable, "I");
+        }
+        Ok(())
+    }
+}
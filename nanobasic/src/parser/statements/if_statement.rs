@@ -1,8 +1,9 @@
 //use std::iter::Peekable;
 use super::{Node, Statement};
 use super::{ParseError, Result};
-use crate::parser::expressions::{Expression, parse_expression};
-use crate::parser::tokenizer::{Token, TokenType};
+use crate::parser::StatementIndex;
+use crate::parser::expressions::{Expression, parse_arithmetic_expression};
+use crate::parser::tokenizer::{Position, Token, TokenType};
 use serde::Serialize;
 use std::iter::Peekable;
 
@@ -17,20 +18,63 @@ pub enum RelationalOperator {
     Greater,
 }
 
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum LogicalOperator {
+    And,
+    Or,
+}
+
+/// <comparison> ::= <expression> <relop> <expression>
 #[derive(Serialize, Debug, PartialEq)]
-pub struct BooleanExpression {
+pub struct Comparison {
     pub operator: RelationalOperator,
     pub left_expr: Node<Expression>,
     pub right_expr: Node<Expression>,
 }
 
-/// 'IF' <boolean-expr> 'THEN' <statement>
+#[derive(Serialize, Debug, PartialEq)]
+pub struct LogicalOperation {
+    pub left: Node<BooleanExpression>,
+    pub right: Node<BooleanExpression>,
+    pub operator: LogicalOperator,
+}
+
+/// <boolean-expr> ::= <and-expr> ('OR' <and-expr>)*
+/// <and-expr>     ::= <not-expr> ('AND' <not-expr>)*
+/// <not-expr>     ::= 'NOT' <not-expr> | <comparison> | '(' <boolean-expr> ')'
+///
+/// - NOT binds tighter than AND, AND binds tighter than OR
+#[derive(Serialize, Debug, PartialEq)]
+pub enum BooleanExpression {
+    Comparison(Box<Comparison>),
+    LogicalOperation(Box<LogicalOperation>),
+    Not(Box<Node<BooleanExpression>>),
+}
+
+/// 'IF' <boolean-expr> 'THEN' <statement>?
+///
+/// Without a statement after 'THEN' the IF starts a block, which is closed
+/// by 'END IF' on one of the following lines
 #[derive(Serialize)]
 #[allow(unused)]
 #[derive(Debug, PartialEq)]
 pub struct IfStatement {
     pub boolean_expr: Node<BooleanExpression>,
-    pub then_statement: Node<Statement>,
+    /// `None` for the head of a block IF
+    pub then_statement: Option<Node<Statement>>,
+    /// The ELSE, ELSEIF or END IF which is executed if the condition is false,
+    /// resolved after parsing. `None` skips the rest of the line.
+    pub else_target: Option<StatementIndex>,
+}
+
+/// 'ELSEIF' <boolean-expr> 'THEN', only inside of a block IF
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ElseIfStatement {
+    pub boolean_expr: Node<BooleanExpression>,
+    /// Like `IfStatement::else_target`
+    pub else_target: Option<StatementIndex>,
+    /// The END IF of the block, where the previous branch continues
+    pub end_target: Option<StatementIndex>,
 }
 
 fn parse_relational_operator<'a, I>(tokens: &mut Peekable<I>) -> Result<RelationalOperator>
@@ -58,70 +102,209 @@ where
     Ok(operator)
 }
 
-fn parse_boolean_expression<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<BooleanExpression>>
+fn parse_comparison<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<BooleanExpression>>
 where
     I: Iterator<Item = &'a Token>,
 {
-    let left_expr = parse_expression(tokens)?;
+    let left_expr = parse_arithmetic_expression(tokens)?;
     let mut position = left_expr.position;
     let operator = parse_relational_operator(tokens)?;
-    let right_expr = parse_expression(tokens)?;
+    let right_expr = parse_arithmetic_expression(tokens)?;
     position.col_end = right_expr.position.col_end;
 
-    let content = BooleanExpression {
+    let comparison = Comparison {
         operator,
         left_expr,
         right_expr,
     };
+    let content = BooleanExpression::Comparison(Box::new(comparison));
     let node = Node { content, position };
     Ok(node)
 }
 
+fn parse_not_expression<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<BooleanExpression>>
+where
+    I: Iterator<Item = &'a Token> + Clone,
+{
+    let Some(&token) = tokens.peek() else {
+        return Err(ParseError::UnexpectedEOF);
+    };
+
+    match token.kind {
+        TokenType::Not => {
+            tokens.next();
+            let operand = parse_not_expression(tokens)?;
+            let position = Position {
+                col_end: operand.position.col_end,
+                ..token.position
+            };
+            let content = BooleanExpression::Not(Box::new(operand));
+            Ok(Node { content, position })
+        }
+        TokenType::OpenParen => {
+            // '(' starts either the left expression of a comparison, like in
+            // '(A + 1) > 2', or a nested condition: try the comparison first
+            let mut lookahead = tokens.clone();
+            if let Ok(comparison) = parse_comparison(&mut lookahead) {
+                *tokens = lookahead;
+                return Ok(comparison);
+            }
+
+            tokens.next();
+            let inner_node = parse_boolean_expression(tokens)?;
+            let close_token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+            let TokenType::CloseParen = close_token.kind else {
+                return Err(ParseError::WrongToken {
+                    expected: ")".to_string(),
+                    actual: format!("{:?}", close_token.kind),
+                });
+            };
+
+            Ok(Node {
+                content: inner_node.content,
+                position: Position {
+                    col_end: close_token.position.col_end,
+                    ..token.position
+                },
+            })
+        }
+        _ => parse_comparison(tokens),
+    }
+}
+
+/// Parses operands separated by the token of `operator`, left associative
+fn parse_logical_operation<'a, I>(
+    tokens: &mut Peekable<I>,
+    operator: LogicalOperator,
+    parse_operand: fn(&mut Peekable<I>) -> Result<Node<BooleanExpression>>,
+) -> Result<Node<BooleanExpression>>
+where
+    I: Iterator<Item = &'a Token> + Clone,
+{
+    let operator_token = match operator {
+        LogicalOperator::And => TokenType::And,
+        LogicalOperator::Or => TokenType::Or,
+    };
+
+    let mut left = parse_operand(tokens)?;
+    while tokens
+        .next_if(|token| token.kind == operator_token)
+        .is_some()
+    {
+        let right = parse_operand(tokens)?;
+        let position = Position {
+            col_end: right.position.col_end,
+            ..left.position
+        };
+        let operation = LogicalOperation {
+            left,
+            right,
+            operator,
+        };
+        let content = BooleanExpression::LogicalOperation(Box::new(operation));
+        left = Node { content, position };
+    }
+    Ok(left)
+}
+
+fn parse_and_expression<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<BooleanExpression>>
+where
+    I: Iterator<Item = &'a Token> + Clone,
+{
+    parse_logical_operation(tokens, LogicalOperator::And, parse_not_expression)
+}
+
+pub fn parse_boolean_expression<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<BooleanExpression>>
+where
+    I: Iterator<Item = &'a Token> + Clone,
+{
+    parse_logical_operation(tokens, LogicalOperator::Or, parse_and_expression)
+}
+
+/// <boolean-expr> 'THEN', the position ends with the THEN
+fn parse_condition<'a, I>(tokens: &mut Peekable<I>) -> Result<(Node<BooleanExpression>, Position)>
+where
+    I: Iterator<Item = &'a Token> + Clone,
+{
+    let boolean_expr = parse_boolean_expression(tokens)?;
+    let mut position = boolean_expr.position;
+    let then_token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+    if then_token.kind != TokenType::Then {
+        return Err(ParseError::WrongToken {
+            expected: "THEN".to_string(),
+            actual: format!("{:?}", then_token.kind),
+        });
+    };
+    position.col_end = then_token.position.col_end;
+    Ok((boolean_expr, position))
+}
+
 impl IfStatement {
     pub fn parse_node<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Self>>
     where
-        I: Iterator<Item = &'a Token>,
+        I: Iterator<Item = &'a Token> + Clone,
     {
-        let boolean_expr = parse_boolean_expression(tokens)?;
-        let mut position = boolean_expr.position;
-        let then_token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
-        if then_token.kind != TokenType::Then {
-            return Err(ParseError::WrongToken {
-                expected: "THEN".to_string(),
-                actual: format!("{:?}", then_token.kind),
-            });
+        let (boolean_expr, mut position) = parse_condition(tokens)?;
+
+        // - A block IF ends the line after THEN
+        let then_statement = match tokens.peek() {
+            None
+            | Some(Token {
+                kind: TokenType::EndOfLine,
+                ..
+            }) => None,
+            Some(_) => {
+                let statement = Statement::parse(tokens)?;
+                position.col_end = statement.position.col_end;
+                Some(statement)
+            }
         };
-        let then_statement = Statement::parse(tokens)?;
-        position.col_end = 0; // TODO: fix
 
         let content = IfStatement {
             boolean_expr,
             then_statement,
+            else_target: None,
         };
-        let node = Node {
-            content,
-            position: position,
-        };
+        let node = Node { content, position };
         Ok(node)
     }
 }
 
+impl ElseIfStatement {
+    pub fn parse_node<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Self>>
+    where
+        I: Iterator<Item = &'a Token> + Clone,
+    {
+        let (boolean_expr, position) = parse_condition(tokens)?;
+        let content = ElseIfStatement {
+            boolean_expr,
+            else_target: None,
+            end_target: None,
+        };
+        Ok(Node { content, position })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::IfStatement;
+    use super::{BooleanExpression, IfStatement, LogicalOperator};
     use super::{Result, parse_boolean_expression};
     use crate::parser::tokenizer::tokenize;
 
     #[test]
     fn test_boolean_expression() -> Result<()> {
         // -- Read input
-        let lines = [vec!["A=3".to_string()], vec!["42>34".to_string()]];
+        let lines = [
+            vec!["A=3".to_string()],
+            vec!["42>34".to_string()],
+            vec!["(A+1)*2 > 3 AND NOT (B < 2 OR C$ = \"x\")".to_string()],
+        ];
 
         for line in &lines {
             println!("{:#?}", line);
 
             println!("* Tokenizing");
-            let tokens = tokenize(&line)?;
+            let tokens = tokenize(line)?;
 
             println!("* Parsing");
             let mut iter_token = tokens.iter().peekable();
@@ -134,13 +317,16 @@ mod tests {
     #[test]
     fn test_if() -> Result<()> {
         // -- Read input
-        let lines = [vec!["IF A=3 THEN GOTO 42".to_string()]];
+        let lines = [
+            vec!["IF A=3 THEN GOTO 42".to_string()],
+            vec!["IF A=3 THEN".to_string()],
+        ];
 
         for line in &lines {
             println!("{:#?}", line);
 
             println!("* Tokenizing");
-            let tokens = tokenize(&line)?;
+            let tokens = tokenize(line)?;
 
             println!("* Parsing");
             let mut iter_token = tokens.iter().peekable();
@@ -150,4 +336,32 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_logical_precedence() -> Result<()> {
+        // NOT binds tighter than AND, AND binds tighter than OR
+        let tokens = tokenize(&["NOT A = 1 OR B = 2 AND (C = 3 OR D = 4)"])?;
+        let result = parse_boolean_expression(&mut tokens.iter().peekable())?;
+        println!("{result:#?}");
+
+        let BooleanExpression::LogicalOperation(or) = result.content else {
+            panic!("Expected OR");
+        };
+        assert_eq!(or.operator, LogicalOperator::Or);
+        assert!(matches!(or.left.content, BooleanExpression::Not(_)));
+
+        let BooleanExpression::LogicalOperation(and) = or.right.content else {
+            panic!("Expected AND");
+        };
+        assert_eq!(and.operator, LogicalOperator::And);
+        assert!(matches!(
+            and.right.content,
+            BooleanExpression::LogicalOperation(ref inner) if inner.operator == LogicalOperator::Or
+        ));
+        assert_eq!(
+            (and.right.position.col_start, and.right.position.col_end),
+            (23, 39)
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,76 @@
+use super::Node;
+use super::let_statment::{LetTarget, parse_targets};
+use super::{ParseError, Result};
+use crate::parser::tokenizer::{Position, Token, TokenType};
+use serde::Serialize;
+use std::iter::Peekable;
+
+/// 'INPUT' (<string> ';')? <target> (',' <target>)*
+#[derive(Serialize, Debug, PartialEq)]
+pub struct InputStatement {
+    pub prompt: Option<String>,
+    pub targets: Vec<Node<LetTarget>>,
+}
+
+impl InputStatement {
+    pub fn parse<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Self>>
+    where
+        I: Iterator<Item = &'a Token>,
+    {
+        let first_token = tokens.peek().ok_or(ParseError::UnexpectedEOF)?;
+        let mut position: Position = first_token.position;
+
+        // - Optional prompt
+        let prompt = if let TokenType::String(prompt) = &first_token.kind {
+            tokens.next();
+            let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+            if token.kind != TokenType::Semicolon {
+                return Err(ParseError::WrongToken {
+                    expected: ";".to_string(),
+                    actual: format!("{:?}", token.kind),
+                });
+            }
+            Some(prompt.clone())
+        } else {
+            None
+        };
+
+        // - Targets
+        let targets = parse_targets(tokens)?;
+        position.col_end = targets.position.col_end;
+
+        let content = InputStatement {
+            prompt,
+            targets: targets.content,
+        };
+        Ok(Node { content, position })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InputStatement;
+    use crate::parser::Result;
+    use crate::parser::tokenizer::tokenize;
+
+    #[test]
+    fn test_input_statement() -> Result<()> {
+        let lines = [
+            (vec!["A".to_string()], None, 1),
+            (vec![r#""Name"; N$, A(2)"#.to_string()], Some("Name"), 2),
+        ];
+
+        for (line, prompt, count) in &lines {
+            println!("{:#?}", line);
+
+            let tokens = tokenize(line)?;
+            let mut iter_token = tokens.iter().peekable();
+            let result = InputStatement::parse(&mut iter_token)?;
+            println!("{result:#?}");
+
+            assert_eq!(result.content.prompt.as_deref(), *prompt);
+            assert_eq!(result.content.targets.len(), *count);
+        }
+        Ok(())
+    }
+}
@@ -1,39 +1,104 @@
 use super::Node;
 use super::{ParseError, Result};
-use crate::parser::expressions::{Expression, parse_expression};
+use crate::parser::builtins::{find_builtin, is_user_function};
+use crate::parser::expressions::{Expression, parse_expression, parse_indices};
 use crate::parser::tokenizer::{Token, TokenType};
 use serde::Serialize;
 use std::iter::Peekable;
 
+/// Receiver of a value: a variable or an array element
+/// <target> ::= <var> | <var> '(' <expression> (',' <expression>)* ')'
 #[derive(Serialize, Debug, PartialEq)]
-pub struct LetStatement {
+pub struct LetTarget {
     pub name: String,
-    pub expression: Node<Expression>,
+    /// Empty for plain variables
+    pub indices: Vec<Node<Expression>>,
 }
 
-impl LetStatement {
+impl LetTarget {
     pub fn parse<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Self>>
     where
         I: Iterator<Item = &'a Token>,
     {
-        // - Variable
-        let mut token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
-
+        let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
         let mut position = token.position;
 
-        let TokenType::Variable(var_name) = &token.kind else {
+        let TokenType::Variable(name) = &token.kind else {
             return Err(ParseError::WrongToken {
                 expected: "Variable".to_string(),
                 actual: format!("{:?}", token.kind),
             });
         };
+        if find_builtin(name).is_some() || is_user_function(name) {
+            return Err(ParseError::WrongToken {
+                expected: "Variable".to_string(),
+                actual: format!("function {}", name.to_uppercase()),
+            });
+        }
+
+        let indices = if let Some(Token {
+            kind: TokenType::OpenParen,
+            ..
+        }) = tokens.peek()
+        {
+            let indices = parse_indices(tokens)?;
+            position.col_end = indices.position.col_end;
+            indices.content
+        } else {
+            Vec::new()
+        };
+
+        let content = LetTarget {
+            name: name.clone(),
+            indices,
+        };
+        Ok(Node { content, position })
+    }
+}
+
+/// <target> (',' <target>)*
+pub fn parse_targets<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Vec<Node<LetTarget>>>>
+where
+    I: Iterator<Item = &'a Token>,
+{
+    let first = LetTarget::parse(tokens)?;
+    let mut position = first.position;
+    let mut targets = vec![first];
+    while tokens
+        .next_if(|token| token.kind == TokenType::Comma)
+        .is_some()
+    {
+        let target = LetTarget::parse(tokens)?;
+        position.col_end = target.position.col_end;
+        targets.push(target);
+    }
+    Ok(Node {
+        content: targets,
+        position,
+    })
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct LetStatement {
+    pub target: Node<LetTarget>,
+    pub expression: Node<Expression>,
+}
+
+impl LetStatement {
+    pub fn parse<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Self>>
+    where
+        I: Iterator<Item = &'a Token>,
+    {
+        // - Variable or array element
+        let target = LetTarget::parse(tokens)?;
+        let mut position = target.position;
 
         // Token Equal
-        token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+        let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
 
         let TokenType::Equal = &token.kind else {
             return Err(ParseError::WrongToken {
-                expected: "Variable".to_string(),
+                expected: "=".to_string(),
                 actual: format!("{:?}", token.kind),
             });
         };
@@ -42,10 +107,7 @@ impl LetStatement {
         let expression = parse_expression(tokens)?;
         position.col_end = expression.position.col_end;
 
-        let content = LetStatement {
-            name: var_name.clone(),
-            expression,
-        };
+        let content = LetStatement { target, expression };
         Ok(Node { content, position })
     }
 }
@@ -68,7 +130,7 @@ mod tests {
 
     #[test]
     fn test_let_statement() {
-        let tokens = vec![
+        let tokens = [
             dummy_token(TokenType::Variable("ABC".to_string())),
             dummy_token(TokenType::Equal),
             dummy_token(TokenType::Number(42)),
@@ -79,4 +141,23 @@ mod tests {
         let result = LetStatement::parse(&mut iter_tokens);
         println!("{:#?}", &result)
     }
+
+    #[test]
+    fn test_let_array_element() {
+        let tokens = [
+            dummy_token(TokenType::Variable("A".to_string())),
+            dummy_token(TokenType::OpenParen),
+            dummy_token(TokenType::Number(1)),
+            dummy_token(TokenType::Comma),
+            dummy_token(TokenType::Number(2)),
+            dummy_token(TokenType::CloseParen),
+            dummy_token(TokenType::Equal),
+            dummy_token(TokenType::Number(42)),
+        ];
+
+        let mut iter_tokens = tokens.iter().peekable();
+        let result = LetStatement::parse(&mut iter_tokens).unwrap();
+        println!("{:#?}", &result);
+        assert_eq!(result.content.target.content.indices.len(), 2);
+    }
 }
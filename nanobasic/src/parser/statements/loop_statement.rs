@@ -0,0 +1,136 @@
+use super::Node;
+use super::Result;
+use super::if_statement::{BooleanExpression, parse_boolean_expression};
+use crate::parser::StatementIndex;
+use crate::parser::tokenizer::{Position, Token, TokenType};
+use serde::Serialize;
+use std::iter::Peekable;
+
+/// 'WHILE' <boolean-expr>
+#[derive(Serialize, Debug, PartialEq)]
+pub struct WhileStatement {
+    pub condition: Node<BooleanExpression>,
+    /// The matching WEND, resolved after parsing
+    pub end_target: Option<StatementIndex>,
+}
+
+/// ('WHILE' | 'UNTIL') <boolean-expr>
+#[derive(Serialize, Debug, PartialEq)]
+pub enum LoopCondition {
+    While(Node<BooleanExpression>),
+    Until(Node<BooleanExpression>),
+}
+
+/// 'DO' <loop-condition>?
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DoStatement {
+    pub condition: Option<LoopCondition>,
+    /// The matching LOOP, resolved after parsing
+    pub end_target: Option<StatementIndex>,
+}
+
+/// 'LOOP' <loop-condition>?
+#[derive(Serialize, Debug, PartialEq)]
+pub struct LoopStatement {
+    pub condition: Option<LoopCondition>,
+    /// The matching DO, resolved after parsing
+    pub start_target: Option<StatementIndex>,
+}
+
+/// Parses an optional condition of DO or LOOP, `position` is extended to its end
+fn parse_loop_condition<'a, I>(
+    tokens: &mut Peekable<I>,
+    position: &mut Position,
+) -> Result<Option<LoopCondition>>
+where
+    I: Iterator<Item = &'a Token> + Clone,
+{
+    let Some(token) =
+        tokens.next_if(|token| matches!(token.kind, TokenType::While | TokenType::Until))
+    else {
+        return Ok(None);
+    };
+
+    let condition = parse_boolean_expression(tokens)?;
+    position.col_end = condition.position.col_end;
+    let condition = if token.kind == TokenType::While {
+        LoopCondition::While(condition)
+    } else {
+        LoopCondition::Until(condition)
+    };
+    Ok(Some(condition))
+}
+
+impl WhileStatement {
+    pub fn parse<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Self>>
+    where
+        I: Iterator<Item = &'a Token> + Clone,
+    {
+        let condition = parse_boolean_expression(tokens)?;
+        let position = condition.position;
+        let content = WhileStatement {
+            condition,
+            end_target: None,
+        };
+        Ok(Node { content, position })
+    }
+}
+
+impl DoStatement {
+    /// Parses the tokens after 'DO', `position` is the position of 'DO'
+    pub fn parse<'a, I>(tokens: &mut Peekable<I>, mut position: Position) -> Result<Node<Self>>
+    where
+        I: Iterator<Item = &'a Token> + Clone,
+    {
+        let condition = parse_loop_condition(tokens, &mut position)?;
+        let content = DoStatement {
+            condition,
+            end_target: None,
+        };
+        Ok(Node { content, position })
+    }
+}
+
+impl LoopStatement {
+    /// Parses the tokens after 'LOOP', `position` is the position of 'LOOP'
+    pub fn parse<'a, I>(tokens: &mut Peekable<I>, mut position: Position) -> Result<Node<Self>>
+    where
+        I: Iterator<Item = &'a Token> + Clone,
+    {
+        let condition = parse_loop_condition(tokens, &mut position)?;
+        let content = LoopStatement {
+            condition,
+            start_target: None,
+        };
+        Ok(Node { content, position })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DoStatement, LoopCondition, LoopStatement};
+    use crate::parser::Result;
+    use crate::parser::tokenizer::tokenize;
+
+    #[test]
+    fn test_loop_conditions() -> Result<()> {
+        let tokens = tokenize(&["DO WHILE A < 3"])?;
+        let mut iter_token = tokens.iter().peekable();
+        let do_token = iter_token.next().expect("Token exists");
+        let result = DoStatement::parse(&mut iter_token, do_token.position)?;
+        println!("{result:#?}");
+        assert!(matches!(
+            result.content.condition,
+            Some(LoopCondition::While(_))
+        ));
+        assert_eq!(result.position.col_end, 14);
+
+        let tokens = tokenize(&["LOOP"])?;
+        let mut iter_token = tokens.iter().peekable();
+        let loop_token = iter_token.next().expect("Token exists");
+        let result = LoopStatement::parse(&mut iter_token, loop_token.position)?;
+        println!("{result:#?}");
+        assert_eq!(result.content.condition, None);
+        Ok(())
+    }
+}
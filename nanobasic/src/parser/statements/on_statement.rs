@@ -0,0 +1,88 @@
+use super::Node;
+use super::{ParseError, Result};
+use crate::parser::expressions::{Expression, parse_expression};
+use crate::parser::tokenizer::{Token, TokenType};
+use serde::Serialize;
+use std::iter::Peekable;
+
+#[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+pub enum JumpKind {
+    GoTo,
+    GoSub,
+}
+
+/// 'ON' <expression> ('GOTO' | 'GOSUB') <expression> (',' <expression>)*
+///
+/// Jumps to the n-th target if the selector is n, other values continue with
+/// the next statement
+#[derive(Serialize, Debug, PartialEq)]
+pub struct OnStatement {
+    pub selector: Node<Expression>,
+    pub kind: JumpKind,
+    pub targets: Vec<Node<Expression>>,
+}
+
+impl OnStatement {
+    pub fn parse<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Self>>
+    where
+        I: Iterator<Item = &'a Token>,
+    {
+        let selector = parse_expression(tokens)?;
+
+        let token = tokens.next().ok_or(ParseError::UnexpectedEOF)?;
+        let kind = match token.kind {
+            TokenType::Goto => JumpKind::GoTo,
+            TokenType::Gosub => JumpKind::GoSub,
+            _ => {
+                return Err(ParseError::WrongToken {
+                    expected: "GOTO or GOSUB".to_string(),
+                    actual: format!("{:?}", token.kind),
+                });
+            }
+        };
+
+        let mut position = selector.position;
+        let mut targets = Vec::new();
+        loop {
+            let target = parse_expression(tokens)?;
+            position.col_end = target.position.col_end;
+            targets.push(target);
+
+            if tokens
+                .next_if(|token| token.kind == TokenType::Comma)
+                .is_none()
+            {
+                break;
+            }
+        }
+
+        let content = OnStatement {
+            selector,
+            kind,
+            targets,
+        };
+        Ok(Node { content, position })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JumpKind, OnStatement};
+    use crate::parser::Result;
+    use crate::parser::tokenizer::tokenize;
+
+    #[test]
+    fn test_on_statement() -> Result<()> {
+        let tokens = tokenize(&["X + 1 GOSUB 100, 200, 300"])?;
+        let result = OnStatement::parse(&mut tokens.iter().peekable())?;
+        assert_eq!(result.content.kind, JumpKind::GoSub);
+        assert_eq!(result.content.targets.len(), 3);
+        assert_eq!(result.position.col_end, 25);
+
+        for line in ["X PRINT 1", "X GOTO", "X GOTO 10,"] {
+            let tokens = tokenize(&[line])?;
+            assert!(OnStatement::parse(&mut tokens.iter().peekable()).is_err());
+        }
+        Ok(())
+    }
+}
@@ -1,17 +1,17 @@
 use super::super::expressions::Expression;
 use super::Node;
-use super::{ParseError, Result};
+use super::Result;
 use crate::{
     parser::expressions::parse_expression,
-    parser::tokenizer::{Position, Token, TokenType},
+    parser::tokenizer::{Token, TokenType},
 };
 use serde::Serialize;
 use std::iter::Peekable;
 
+/// Strings are printed via string expressions
 #[derive(Serialize, Debug, PartialEq)]
 pub enum Printable {
-    String(String),
-    ExpressionNode(Box<Expression>),
+    ExpressionNode(Box<Node<Expression>>),
 }
 
 pub type Printables = Vec<Node<Printable>>;
@@ -20,23 +20,9 @@ fn parse_one_printable<'a, I>(tokens: &mut Peekable<I>) -> Result<Node<Printable
 where
     I: Iterator<Item = &'a Token>,
 {
-    let position: Position;
-    let token_preview = tokens.peek().ok_or(ParseError::UnexpectedEOF)?;
-    let content = match token_preview.kind {
-        TokenType::String(ref str) => {
-            let token = tokens.next().expect("Token was peeked, now not found ??");
-            position = token.position;
-            Printable::String(str.clone())
-        }
-        _ => {
-            let Node {
-                content,
-                position: pos,
-            } = parse_expression(tokens)?;
-            position = pos;
-            Printable::ExpressionNode(Box::new(content))
-        }
-    };
+    let expression = parse_expression(tokens)?;
+    let position = expression.position;
+    let content = Printable::ExpressionNode(Box::new(expression));
     let node = Node { content, position };
 
     Ok(node)
@@ -82,7 +68,7 @@ mod tests {
             println!("{:#?}", printable);
 
             println!("* Tokenizing");
-            let tokens = tokenize(&vec![printable.clone()])?;
+            let tokens = tokenize(std::slice::from_ref(printable))?;
 
             println!("* Parsing");
             let mut iter_token = tokens.iter().peekable();
@@ -100,7 +86,7 @@ mod tests {
             println!("{:#?}", printable);
 
             println!("* Tokenizing");
-            let tokens = tokenize(&vec![printable.clone()])?;
+            let tokens = tokenize(std::slice::from_ref(printable))?;
             println!("tokens = {tokens:#?}");
 
             println!("* Parsing");
@@ -0,0 +1,49 @@
+use super::Node;
+use super::Result;
+use crate::parser::expressions::{Expression, parse_expression};
+use crate::parser::tokenizer::{Position, Token, TokenType};
+use serde::Serialize;
+use std::iter::Peekable;
+
+/// 'RESUME' ('NEXT' | <expression>)?
+///
+/// Where the program continues after an error was handled
+#[derive(Serialize, Debug, PartialEq)]
+pub enum ResumeTarget {
+    /// Executes the failed statement again
+    Retry,
+    /// Continues with the statement after the failed one
+    Next,
+    /// Continues at a line
+    Line(Box<Node<Expression>>),
+}
+
+impl ResumeTarget {
+    /// Parses the tokens after RESUME, `position` is the position of RESUME
+    pub fn parse<'a, I>(tokens: &mut Peekable<I>, mut position: Position) -> Result<Node<Self>>
+    where
+        I: Iterator<Item = &'a Token>,
+    {
+        let content = match tokens.peek() {
+            None
+            | Some(Token {
+                kind: TokenType::EndOfLine | TokenType::Colon | TokenType::Else,
+                ..
+            }) => ResumeTarget::Retry,
+            Some(Token {
+                kind: TokenType::Next,
+                position: next_position,
+            }) => {
+                tokens.next();
+                position.col_end = next_position.col_end;
+                ResumeTarget::Next
+            }
+            Some(_) => {
+                let line_id = parse_expression(tokens)?;
+                position.col_end = line_id.position.col_end;
+                ResumeTarget::Line(Box::new(line_id))
+            }
+        };
+        Ok(Node { content, position })
+    }
+}
@@ -1,20 +1,54 @@
 use super::{ParseError, Result};
-use once_cell::sync::Lazy;
-use regex::Regex;
 use serde::Serialize;
+use std::fmt;
 
-#[derive(Serialize, Debug, PartialEq)]
+#[cfg(feature = "regex-reference")]
+mod regex_reference;
+mod scanner;
+
+#[cfg(feature = "regex-reference")]
+pub use regex_reference::tokenize_with_regex;
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
 pub enum TokenType {
     Comment,
     Whitespace,
     Print,
     If,
     Then,
+    And,
+    Or,
+    Not,
+    ElseIf,
+    Else,
+    End,
     Let,
     Goto,
     Gosub,
     Return,
+    Stop,
+    Randomize,
+    Def,
+    Data,
+    Read,
+    Restore,
+    On,
+    Error,
+    Resume,
+    For,
+    To,
+    Step,
+    Next,
+    Dim,
+    Input,
+    While,
+    Wend,
+    Do,
+    Loop,
+    Until,
     Comma,
+    Semicolon,
+    Colon,
     Equal,
     NotEqual,
     LessEqual,
@@ -25,13 +59,34 @@ pub enum TokenType {
     Minus,
     Multiply,
     Divide,
+    IntegerDivide,
+    Power,
+    Mod,
+    Xor,
     OpenParen,
     CloseParen,
+    /// End of a physical line, emitted by `tokenize` and not matched by any case
+    EndOfLine,
     Variable(String),
+    /// Integer literal, never negative and at most `isize::MAX`. A leading
+    /// minus is a separate token, negation is done by the parser.
     Number(usize),
+    Float(f64),
     String(String),
 }
 
+/// How keywords are separated from names
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum KeywordMode {
+    /// A word of letters is a keyword only as a whole, keywords can not be
+    /// used as names: `PRINTER` is a variable, `REM` must stand alone
+    #[default]
+    Reserved,
+    /// Classic crunched listings: every word starting with a keyword is
+    /// split after it, `FORI=1TO9` is `FOR I = 1 TO 9`
+    Crunched,
+}
+
 const IGNORE_TOKEN_TYPES: &[TokenType] = &[TokenType::Whitespace, TokenType::Comment];
 
 #[derive(Clone, Copy, Serialize, Debug, PartialEq)]
@@ -41,120 +96,102 @@ pub struct Position {
     pub col_end: usize,
 }
 
+/// Human readable position, counting lines and columns from 1
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, columns {}-{}",
+            self.line_num + 1,
+            self.col_start + 1,
+            self.col_end
+        )
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Token {
     pub kind: TokenType,
     pub position: Position,
 }
 
-#[allow(unused)]
-struct Case {
-    regex_str: String,
-    regex: Regex,
-    capture: bool,
-    ctor: fn(&str) -> TokenType,
+/// Token of digits, `None` if the number does not fit an integer
+fn integer_literal(digits: &str) -> Option<TokenType> {
+    let number = digits.parse().ok()?;
+    (number <= isize::MAX as usize).then_some(TokenType::Number(number))
 }
 
-macro_rules! case {
-    ($regex:expr, $capture:expr, $ctor:expr) => {
-        Case {
-            regex_str: $regex.to_string(),
-            regex: Regex::new(concat!("^", $regex)).unwrap(),
-            capture: $capture,
-            ctor: $ctor,
-        }
-    };
+/// Token of a decimal number, `None` if it is too large for a float
+fn float_literal(decimal: &str) -> Option<TokenType> {
+    let number: f64 = decimal.parse().ok()?;
+    number.is_finite().then_some(TokenType::Float(number))
 }
 
-static CASES: Lazy<[Case; 25]> = Lazy::new(|| {
-    [
-        case!(r"(?i)rem.*", false, |_v| TokenType::Comment),
-        case!(r"[ \t\n\r]", false, |_v| TokenType::Whitespace),
-        case!(r"(?i)print", false, |_v| TokenType::Print),
-        case!(r"(?i)if", false, |_v| TokenType::If),
-        case!(r"(?i)then", false, |_v| TokenType::Then),
-        case!(r"(?i)let", false, |_v| TokenType::Let),
-        case!(r"(?i)goto", false, |_v| TokenType::Goto),
-        case!(r"(?i)gosub", false, |_v| TokenType::Gosub),
-        case!(r"(?i)return", false, |_v| TokenType::Return),
-        case!(r",", false, |_v| TokenType::Comma),
-        case!(r"=", false, |_v| TokenType::Equal),
-        case!(r"<>|><", false, |_v| TokenType::NotEqual),
-        case!(r"<=", false, |_v| TokenType::LessEqual),
-        case!(r">=", false, |_v| TokenType::GreaterEqual),
-        case!(r"<", false, |_v| TokenType::Less),
-        case!(r">", false, |_v| TokenType::Greater),
-        case!(r"\+", false, |_v| TokenType::Plus),
-        case!(r"-", false, |_v| TokenType::Minus),
-        case!(r"\*", false, |_v| TokenType::Multiply),
-        case!(r"/", false, |_v| TokenType::Divide),
-        case!(r"\(", false, |_v| TokenType::OpenParen),
-        case!(r"\)", false, |_v| TokenType::CloseParen),
-        case!(r"[A-Za-z_]+", true, |v| TokenType::Variable(v.to_string())),
-        case!(r"-?[0-9]+", true, |v| TokenType::Number(v.parse().unwrap())),
-        case!(r#"".*""#, true, |v| {
-            let mut x = v.to_string();
-            x.pop();
-            x.remove(0);
-            TokenType::String(x)
-        }),
-    ]
-});
-
-fn match_token(text: &str, col_start: usize, line_num: usize) -> Result<Token> {
-    let token = CASES.iter().find_map(|case| {
-        let m = case.regex.find(text)?;
-        let content = &text[m.start()..m.end()];
-
-        Some(Token {
-            kind: (case.ctor)(content),
-            position: Position {
-                line_num,
-                col_start: m.start() + col_start,
-                col_end: m.end() + col_start,
-            },
-        })
-    });
+/// Token of a string in quotes, in which `""` stands for a quote
+fn string_literal(quoted: &str) -> TokenType {
+    let content = &quoted[1..quoted.len() - 1];
+    TokenType::String(content.replace(r#""""#, r#"""#))
+}
 
-    token.ok_or(ParseError::UnkownToken {
+/// Error for a string without closing quote, `position` is the opening quote
+fn unterminated_string(line_num: usize, col_start: usize) -> ParseError {
+    let position = Position {
         line_num,
         col_start,
-        unkown_code: text.to_string(),
-    })
+        col_end: col_start + 1,
+    };
+    ParseError::UnterminatedString { position }
 }
 
-fn tokenize_line(line: &str, line_num: usize) -> Result<Vec<Token>> {
-    let mut tokens = Vec::<Token>::new();
-    let mut col = 0;
-    while line.len() > col {
-        let mut token = match_token(&line[col..], col, line_num)?;
-        let offset = token.position.col_end - token.position.col_start;
-
-        if !IGNORE_TOKEN_TYPES.contains(&token.kind) {
-            token.position.line_num = line_num;
-            tokens.push(token);
-        }
-
-        col += offset;
+fn number_too_large(literal: &str, position: Position) -> ParseError {
+    ParseError::NumberTooLarge {
+        literal: literal.to_string(),
+        position,
     }
-    Ok(tokens)
 }
 
 /// Translate input into a Vec of Tokens
+///
+/// Every line containing tokens is terminated by an `EndOfLine` token,
+/// lines without tokens (empty or comment only) are skipped entirely
 pub fn tokenize(lines: &[impl AsRef<str>]) -> Result<Vec<Token>> {
-    let tokens = lines
-        .iter()
-        .enumerate()
-        .map(|(i, line)| tokenize_line(line.as_ref(), i))
-        .collect::<Result<Vec<_>>>()?
-        .into_iter()
-        .flatten()
-        .collect();
+    tokenize_with(lines, KeywordMode::default())
+}
+
+/// Same as `tokenize`, with the given separation of keywords and names
+pub fn tokenize_with(lines: &[impl AsRef<str>], mode: KeywordMode) -> Result<Vec<Token>> {
+    tokenize_lines(lines, |line, line_num| {
+        scanner::scan_line(line, line_num, mode)
+    })
+}
+
+fn tokenize_lines(
+    lines: &[impl AsRef<str>],
+    tokenize_line: impl Fn(&str, usize) -> Result<Vec<Token>>,
+) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    for (line_num, line) in lines.iter().enumerate() {
+        let line = line.as_ref();
+        let line_tokens = tokenize_line(line, line_num)?;
+        if line_tokens.is_empty() {
+            continue;
+        }
+
+        tokens.extend(line_tokens);
+        tokens.push(Token {
+            kind: TokenType::EndOfLine,
+            position: Position {
+                line_num,
+                col_start: line.len(),
+                col_end: line.len(),
+            },
+        });
+    }
 
     Ok(tokens)
 }
 
-// Outsource Unittests to extra file:
-#[cfg(test)]
+// Outsource Unittests to extra file, they compare with the regex reference:
+#[cfg(all(test, feature = "regex-reference"))]
 #[path = "tokenizer_tests.rs"]
 mod tests;
@@ -0,0 +1,162 @@
+//! Tokenizer matching the tokens with regular expressions, which the
+//! scanner replaced. Kept as reference for tests and benchmarks of the
+//! scanner, it produces the same tokens as the scanner in crunched mode.
+
+use super::{
+    IGNORE_TOKEN_TYPES, Position, Token, TokenType, float_literal, integer_literal,
+    number_too_large, string_literal, tokenize_lines, unterminated_string,
+};
+use crate::parser::{ParseError, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+#[allow(unused)]
+struct Case {
+    regex_str: String,
+    regex: Regex,
+    capture: bool,
+    /// `None` if the matched number is too large
+    ctor: fn(&str) -> Option<TokenType>,
+}
+
+macro_rules! case {
+    ($regex:expr, $capture:expr, $ctor:expr) => {
+        fallible_case!($regex, $capture, |v| Some(($ctor)(v)))
+    };
+}
+
+macro_rules! fallible_case {
+    ($regex:expr, $capture:expr, $ctor:expr) => {
+        Case {
+            regex_str: $regex.to_string(),
+            regex: Regex::new(concat!("^(?:", $regex, ")")).unwrap(),
+            capture: $capture,
+            ctor: $ctor,
+        }
+    };
+}
+
+static CASES: Lazy<[Case; 58]> = Lazy::new(|| {
+    [
+        case!(r"(?i)rem.*", false, |_v| TokenType::Comment),
+        case!(r"[ \t\n\r]", false, |_v| TokenType::Whitespace),
+        case!(r"(?i)print", false, |_v| TokenType::Print),
+        case!(r"(?i)if", false, |_v| TokenType::If),
+        case!(r"(?i)then", false, |_v| TokenType::Then),
+        case!(r"(?i)and", false, |_v| TokenType::And),
+        case!(r"(?i)or", false, |_v| TokenType::Or),
+        case!(r"(?i)xor", false, |_v| TokenType::Xor),
+        case!(r"(?i)mod", false, |_v| TokenType::Mod),
+        case!(r"(?i)not", false, |_v| TokenType::Not),
+        case!(r"(?i)elseif", false, |_v| TokenType::ElseIf),
+        case!(r"(?i)else", false, |_v| TokenType::Else),
+        case!(r"(?i)end", false, |_v| TokenType::End),
+        case!(r"(?i)let", false, |_v| TokenType::Let),
+        case!(r"(?i)goto", false, |_v| TokenType::Goto),
+        case!(r"(?i)gosub", false, |_v| TokenType::Gosub),
+        case!(r"(?i)return", false, |_v| TokenType::Return),
+        case!(r"(?i)stop", false, |_v| TokenType::Stop),
+        case!(r"(?i)randomize", false, |_v| TokenType::Randomize),
+        case!(r"(?i)def", false, |_v| TokenType::Def),
+        case!(r"(?i)data", false, |_v| TokenType::Data),
+        case!(r"(?i)read", false, |_v| TokenType::Read),
+        case!(r"(?i)restore", false, |_v| TokenType::Restore),
+        case!(r"(?i)on", false, |_v| TokenType::On),
+        case!(r"(?i)error", false, |_v| TokenType::Error),
+        case!(r"(?i)resume", false, |_v| TokenType::Resume),
+        case!(r"(?i)for", false, |_v| TokenType::For),
+        case!(r"(?i)to", false, |_v| TokenType::To),
+        case!(r"(?i)step", false, |_v| TokenType::Step),
+        case!(r"(?i)next", false, |_v| TokenType::Next),
+        case!(r"(?i)dim", false, |_v| TokenType::Dim),
+        case!(r"(?i)input", false, |_v| TokenType::Input),
+        case!(r"(?i)while", false, |_v| TokenType::While),
+        case!(r"(?i)wend", false, |_v| TokenType::Wend),
+        case!(r"(?i)do", false, |_v| TokenType::Do),
+        case!(r"(?i)loop", false, |_v| TokenType::Loop),
+        case!(r"(?i)until", false, |_v| TokenType::Until),
+        case!(r",", false, |_v| TokenType::Comma),
+        case!(r";", false, |_v| TokenType::Semicolon),
+        case!(r":", false, |_v| TokenType::Colon),
+        case!(r"=", false, |_v| TokenType::Equal),
+        case!(r"<>|><", false, |_v| TokenType::NotEqual),
+        case!(r"<=", false, |_v| TokenType::LessEqual),
+        case!(r">=", false, |_v| TokenType::GreaterEqual),
+        case!(r"<", false, |_v| TokenType::Less),
+        case!(r">", false, |_v| TokenType::Greater),
+        case!(r"\+", false, |_v| TokenType::Plus),
+        case!(r"-", false, |_v| TokenType::Minus),
+        case!(r"\*", false, |_v| TokenType::Multiply),
+        case!(r"/", false, |_v| TokenType::Divide),
+        case!(r"\\", false, |_v| TokenType::IntegerDivide),
+        case!(r"\^", false, |_v| TokenType::Power),
+        case!(r"\(", false, |_v| TokenType::OpenParen),
+        case!(r"\)", false, |_v| TokenType::CloseParen),
+        case!(r"[A-Za-z_]+\$?", true, |v: &str| TokenType::Variable(
+            v.to_string()
+        )),
+        fallible_case!(
+            r"([0-9]+\.[0-9]*|\.[0-9]+)([eE][-+]?[0-9]+)?|[0-9]+[eE][-+]?[0-9]+",
+            true,
+            float_literal
+        ),
+        fallible_case!(r"[0-9]+", true, integer_literal),
+        case!(r#""([^"\n]|"")*""#, true, string_literal),
+    ]
+});
+
+pub(super) fn match_token(text: &str, col_start: usize, line_num: usize) -> Result<Token> {
+    let token = CASES.iter().find_map(|case| {
+        let m = case.regex.find(text)?;
+        let content = &text[m.start()..m.end()];
+        // A string followed by a quote only matches by backtracking over a
+        // doubled quote at its end, so it has no closing quote
+        if content.starts_with('"') && text[m.end()..].starts_with('"') {
+            return Some(Err(unterminated_string(line_num, col_start)));
+        }
+        let position = Position {
+            line_num,
+            col_start: m.start() + col_start,
+            col_end: m.end() + col_start,
+        };
+
+        Some(match (case.ctor)(content) {
+            Some(kind) => Ok(Token { kind, position }),
+            None => Err(number_too_large(content, position)),
+        })
+    });
+
+    token.unwrap_or_else(|| {
+        if text.starts_with('"') {
+            return Err(unterminated_string(line_num, col_start));
+        }
+        Err(ParseError::UnkownToken {
+            line_num,
+            col_start,
+            unkown_code: text.to_string(),
+        })
+    })
+}
+
+pub(super) fn tokenize_line(line: &str, line_num: usize) -> Result<Vec<Token>> {
+    let mut tokens = Vec::<Token>::new();
+    let mut col = 0;
+    while line.len() > col {
+        let mut token = match_token(&line[col..], col, line_num)?;
+        let offset = token.position.col_end - token.position.col_start;
+
+        if !IGNORE_TOKEN_TYPES.contains(&token.kind) {
+            token.position.line_num = line_num;
+            tokens.push(token);
+        }
+
+        col += offset;
+    }
+    Ok(tokens)
+}
+
+/// Same as `tokenize` in crunched mode, but matches the tokens with the
+/// regex cases. It is much slower than the scanner.
+pub fn tokenize_with_regex(lines: &[impl AsRef<str>]) -> Result<Vec<Token>> {
+    tokenize_lines(lines, tokenize_line)
+}
@@ -0,0 +1,395 @@
+//! Hand-written scanner, which splits a line in a single pass over its bytes.
+//! In crunched mode it produces the same tokens as the regex cases.
+
+use super::{
+    IGNORE_TOKEN_TYPES, KeywordMode, Position, Token, TokenType, float_literal, integer_literal,
+    number_too_large, string_literal, unterminated_string,
+};
+use crate::parser::{ParseError, Result};
+use std::result;
+
+/// Keywords in the order of the regex cases. In crunched mode keywords are
+/// recognized at the start of a word even if letters follow, so the first
+/// matching entry wins: "PRINTX" is PRINT followed by the variable X.
+static KEYWORDS: [(&str, TokenType); 35] = [
+    ("print", TokenType::Print),
+    ("if", TokenType::If),
+    ("then", TokenType::Then),
+    ("and", TokenType::And),
+    ("or", TokenType::Or),
+    ("xor", TokenType::Xor),
+    ("mod", TokenType::Mod),
+    ("not", TokenType::Not),
+    ("elseif", TokenType::ElseIf),
+    ("else", TokenType::Else),
+    ("end", TokenType::End),
+    ("let", TokenType::Let),
+    ("goto", TokenType::Goto),
+    ("gosub", TokenType::Gosub),
+    ("return", TokenType::Return),
+    ("stop", TokenType::Stop),
+    ("randomize", TokenType::Randomize),
+    ("def", TokenType::Def),
+    ("data", TokenType::Data),
+    ("read", TokenType::Read),
+    ("restore", TokenType::Restore),
+    ("on", TokenType::On),
+    ("error", TokenType::Error),
+    ("resume", TokenType::Resume),
+    ("for", TokenType::For),
+    ("to", TokenType::To),
+    ("step", TokenType::Step),
+    ("next", TokenType::Next),
+    ("dim", TokenType::Dim),
+    ("input", TokenType::Input),
+    ("while", TokenType::While),
+    ("wend", TokenType::Wend),
+    ("do", TokenType::Do),
+    ("loop", TokenType::Loop),
+    ("until", TokenType::Until),
+];
+
+/// Why no token could be scanned
+enum ScanError {
+    /// No token starts at the position
+    Unknown,
+    /// Number with the given length in bytes, which is too large
+    NumberTooLarge(usize),
+    /// String without closing quote on the line
+    UnterminatedString,
+}
+
+/// Splits a line into tokens, whitespace and comments are dropped
+pub(super) fn scan_line(line: &str, line_num: usize, mode: KeywordMode) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut col = 0;
+    while col < line.len() {
+        let (kind, len) =
+            scan_token(&line.as_bytes()[col..], mode).map_err(|error| match error {
+                ScanError::Unknown => ParseError::UnkownToken {
+                    line_num,
+                    col_start: col,
+                    unkown_code: line[col..].to_string(),
+                },
+                ScanError::NumberTooLarge(len) => {
+                    let position = Position {
+                        line_num,
+                        col_start: col,
+                        col_end: col + len,
+                    };
+                    number_too_large(&line[col..col + len], position)
+                }
+                ScanError::UnterminatedString => unterminated_string(line_num, col),
+            })?;
+
+        if !IGNORE_TOKEN_TYPES.contains(&kind) {
+            tokens.push(Token {
+                kind,
+                position: Position {
+                    line_num,
+                    col_start: col,
+                    col_end: col + len,
+                },
+            });
+        }
+        col += len;
+    }
+    Ok(tokens)
+}
+
+/// Kind and length in bytes of the token at the start of `text`. `text` must
+/// not be empty.
+fn scan_token(text: &[u8], mode: KeywordMode) -> result::Result<(TokenType, usize), ScanError> {
+    let single = |kind| Ok((kind, 1));
+    match text[0] {
+        b' ' | b'\t' | b'\n' | b'\r' => single(TokenType::Whitespace),
+        b',' => single(TokenType::Comma),
+        b';' => single(TokenType::Semicolon),
+        b':' => single(TokenType::Colon),
+        b'=' => single(TokenType::Equal),
+        b'<' => match text.get(1) {
+            Some(b'>') => Ok((TokenType::NotEqual, 2)),
+            Some(b'=') => Ok((TokenType::LessEqual, 2)),
+            _ => single(TokenType::Less),
+        },
+        b'>' => match text.get(1) {
+            Some(b'<') => Ok((TokenType::NotEqual, 2)),
+            Some(b'=') => Ok((TokenType::GreaterEqual, 2)),
+            _ => single(TokenType::Greater),
+        },
+        b'+' => single(TokenType::Plus),
+        b'-' => single(TokenType::Minus),
+        b'*' => single(TokenType::Multiply),
+        b'/' => single(TokenType::Divide),
+        b'\\' => single(TokenType::IntegerDivide),
+        b'^' => single(TokenType::Power),
+        b'(' => single(TokenType::OpenParen),
+        b')' => single(TokenType::CloseParen),
+        b'A'..=b'Z' | b'a'..=b'z' | b'_' => Ok(scan_word(text, mode)),
+        b'0'..=b'9' | b'.' => scan_number(text),
+        b'"' => scan_string(text).ok_or(ScanError::UnterminatedString),
+        _ => Err(ScanError::Unknown),
+    }
+}
+
+fn starts_with_ignore_case(text: &[u8], prefix: &str) -> bool {
+    text.get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix.as_bytes()))
+}
+
+/// Comment, keyword or variable
+fn scan_word(text: &[u8], mode: KeywordMode) -> (TokenType, usize) {
+    let mut len = count_while(text, |c| c.is_ascii_alphabetic() || c == b'_');
+    let is_keyword = |keyword: &str| match mode {
+        KeywordMode::Reserved => {
+            text[..len].eq_ignore_ascii_case(keyword.as_bytes()) && text.get(len) != Some(&b'$')
+        }
+        KeywordMode::Crunched => starts_with_ignore_case(text, keyword),
+    };
+
+    // A comment reaches to the end of the line
+    if is_keyword("rem") {
+        let len = text.iter().position(|&c| c == b'\n').unwrap_or(text.len());
+        return (TokenType::Comment, len);
+    }
+
+    if let Some((keyword, kind)) = KEYWORDS.iter().find(|(keyword, _)| is_keyword(keyword)) {
+        return (kind.clone(), keyword.len());
+    }
+
+    if text.get(len) == Some(&b'$') {
+        len += 1;
+    }
+    let name = str::from_utf8(&text[..len]).expect("Name is ASCII");
+    (TokenType::Variable(name.to_string()), len)
+}
+
+/// Integer like 42 or float like 3.14, .5, 1. or 1E-3
+fn scan_number(text: &[u8]) -> result::Result<(TokenType, usize), ScanError> {
+    let digits = count_while(text, |c| c.is_ascii_digit());
+    let len = match text.get(digits) {
+        Some(b'.') => {
+            let fraction = count_while(&text[digits + 1..], |c| c.is_ascii_digit());
+            if digits == 0 && fraction == 0 {
+                return Err(ScanError::Unknown);
+            }
+            let len = digits + 1 + fraction;
+            len + exponent_len(&text[len..])
+        }
+        _ => match exponent_len(&text[digits..]) {
+            0 => {
+                let number = str::from_utf8(&text[..digits]).expect("Digits are ASCII");
+                let kind = integer_literal(number).ok_or(ScanError::NumberTooLarge(digits))?;
+                return Ok((kind, digits));
+            }
+            exponent => digits + exponent,
+        },
+    };
+
+    let number = str::from_utf8(&text[..len]).expect("Number is ASCII");
+    let kind = float_literal(number).ok_or(ScanError::NumberTooLarge(len))?;
+    Ok((kind, len))
+}
+
+/// Length of an exponent like E5 or e-3, 0 if `text` doesn't start with one
+fn exponent_len(text: &[u8]) -> usize {
+    if !matches!(text.first(), Some(b'e' | b'E')) {
+        return 0;
+    }
+    let sign = usize::from(matches!(text.get(1), Some(b'+' | b'-')));
+    match count_while(&text[1 + sign..], |c| c.is_ascii_digit()) {
+        0 => 0,
+        digits => 1 + sign + digits,
+    }
+}
+
+/// String up to the next single quote on the line, a doubled quote `""`
+/// stands for a quote in the string. `None` if there is no closing quote.
+fn scan_string(text: &[u8]) -> Option<(TokenType, usize)> {
+    let mut len = 1;
+    loop {
+        match text.get(len)? {
+            b'"' if text.get(len + 1) == Some(&b'"') => len += 2,
+            b'"' => break,
+            b'\n' => return None,
+            _ => len += 1,
+        }
+    }
+    let quoted = str::from_utf8(&text[..=len]).expect("Quotes are ASCII, so the string is UTF-8");
+    Some((string_literal(quoted), len + 1))
+}
+
+fn count_while(text: &[u8], predicate: impl Fn(u8) -> bool) -> usize {
+    text.iter().take_while(|&&c| predicate(c)).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scan_line;
+    use crate::parser::ParseError;
+    #[cfg(feature = "regex-reference")]
+    use crate::parser::tokenizer::regex_reference::tokenize_line;
+    use crate::parser::tokenizer::{KeywordMode, TokenType};
+
+    fn kinds(line: &str, mode: KeywordMode) -> Vec<TokenType> {
+        let tokens = scan_line(line, 0, mode).unwrap();
+        tokens.into_iter().map(|token| token.kind).collect()
+    }
+
+    fn variable(name: &str) -> TokenType {
+        TokenType::Variable(name.to_string())
+    }
+
+    #[test]
+    fn test_reserved_words() {
+        let mode = KeywordMode::Reserved;
+        assert_eq!(
+            kinds("PRINTER = 1", mode),
+            [variable("PRINTER"), TokenType::Equal, TokenType::Number(1)]
+        );
+        assert_eq!(
+            kinds("REMAINDER = TOTAL MOD 3", mode),
+            [
+                variable("REMAINDER"),
+                TokenType::Equal,
+                variable("TOTAL"),
+                TokenType::Mod,
+                TokenType::Number(3)
+            ]
+        );
+        assert_eq!(
+            kinds("Print Ends: goto10", mode),
+            [
+                TokenType::Print,
+                variable("Ends"),
+                TokenType::Colon,
+                TokenType::Goto,
+                TokenType::Number(10)
+            ]
+        );
+        assert_eq!(
+            kinds("ELSEIF ENDIF END$", mode)[1..],
+            [variable("ENDIF"), variable("END$")]
+        );
+        assert_eq!(kinds("rem: PRINT X", mode), [TokenType::Comment; 0]);
+        assert_eq!(kinds("REMARK", mode), [variable("REMARK")]);
+    }
+
+    #[test]
+    fn test_crunched_keywords() {
+        let mode = KeywordMode::Crunched;
+        assert_eq!(
+            kinds("PRINTER = 1", mode),
+            [
+                TokenType::Print,
+                variable("ER"),
+                TokenType::Equal,
+                TokenType::Number(1)
+            ]
+        );
+        assert_eq!(
+            kinds("FORI=1TO9:NEXTI", mode),
+            [
+                TokenType::For,
+                variable("I"),
+                TokenType::Equal,
+                TokenType::Number(1),
+                TokenType::To,
+                TokenType::Number(9),
+                TokenType::Colon,
+                TokenType::Next,
+                variable("I")
+            ]
+        );
+        assert!(kinds("REMAINDER = 1", mode).is_empty());
+    }
+
+    #[test]
+    fn test_number_literals() {
+        let mode = KeywordMode::Reserved;
+        assert_eq!(
+            kinds("A-1", mode),
+            [variable("A"), TokenType::Minus, TokenType::Number(1)]
+        );
+        assert_eq!(
+            kinds("-9223372036854775807", mode),
+            [TokenType::Minus, TokenType::Number(isize::MAX as usize)]
+        );
+
+        for (line, col_end) in [("X = 9223372036854775808", 23), ("X = 2E308 + 1", 9)] {
+            let result = scan_line(line, 0, mode);
+            let Err(ParseError::NumberTooLarge { position, .. }) = result else {
+                panic!("{line}: {result:?}");
+            };
+            assert_eq!((position.col_start, position.col_end), (4, col_end));
+        }
+    }
+
+    #[test]
+    fn test_string_literals() {
+        let string = |text: &str| TokenType::String(text.to_string());
+        assert_eq!(
+            kinds(r#""A", X, "B""#, KeywordMode::Reserved),
+            [
+                string("A"),
+                TokenType::Comma,
+                variable("X"),
+                TokenType::Comma,
+                string("B")
+            ]
+        );
+        assert_eq!(
+            kinds(r#""""" + "say ""hi""""#, KeywordMode::Reserved),
+            [string("\""), TokenType::Plus, string("say \"hi\"")]
+        );
+
+        for (line, col_start) in [(r#"X = "A"#, 4), (r#""A" + "B"" + 1"#, 6)] {
+            let result = scan_line(line, 2, KeywordMode::Reserved);
+            let Err(ParseError::UnterminatedString { position }) = result else {
+                panic!("{line}: {result:?}");
+            };
+            assert_eq!(
+                (position.line_num, position.col_start, position.col_end),
+                (2, col_start, col_start + 1)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "regex-reference")]
+    fn test_crunched_scanner_matches_regex() {
+        let lines = [
+            "10 PRINT \"Hello, World\"",
+            "20 IF A<>B AND C><D OR E<=F THEN PRINTX: GOTO 10 ELSE GOSUB 100",
+            "30 LET N$ = \"a \"quoted\" word\" : REM a comment \"with quotes\"",
+            "40 FORI=1TO10STEP2:NEXTI",
+            "50 X = 1.5 + .25 - 3. * 1E5 / 2.5e-3 \\ 7 ^ 1E+2 MOD 3",
+            "60 X = 1E + 1.5E - 2e+",
+            "70 remainder _A_B$ ELSEIF ENDIF ORDER XORMOD",
+            "\t80  DATA -1, 2.0, \"three\"\r",
+            "90 \"unterminated",
+            "100 PRINT .",
+            "110 PRINT 2 # 3",
+            "120 PRINT \"é\"; é",
+            "130 PRINT 9223372036854775807 + 1",
+            "140 PRINT 9223372036854775808 + 1",
+            "150 PRINT 1E308 * 1.8E308",
+            "160 PRINT 99999999999999999999999",
+            "170 PRINT \"A\", X, \"B\"; \"\"; \"say \"\"hi\"\"\"",
+            "180 PRINT \"A\" + \"B",
+            "190 PRINT \"A\"\"",
+            "200 PRINT \"A\"\"\"",
+        ];
+        for line in lines {
+            let expected = tokenize_line(line, 3);
+            let actual = scan_line(line, 3, KeywordMode::Crunched);
+            match (expected, actual) {
+                (Ok(expected), Ok(actual)) => assert_eq!(actual, expected, "{line}"),
+                (Err(expected), Err(actual)) => {
+                    assert_eq!(actual.to_string(), expected.to_string(), "{line}")
+                }
+                (expected, actual) => panic!("{line}: {actual:?} instead of {expected:?}"),
+            }
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::regex_reference::{match_token, tokenize_line};
 use super::*;
 
 #[test]
@@ -58,11 +59,33 @@ fn test_match_token() {
                 },
             },
         ),
+        (
+            r"3.25E-2",
+            Token {
+                kind: TokenType::Float(0.0325),
+                position: Position {
+                    line_num: 0,
+                    col_start: 0,
+                    col_end: 7,
+                },
+            },
+        ),
+        (
+            r"N$",
+            Token {
+                kind: TokenType::Variable("N$".to_string()),
+                position: Position {
+                    line_num: 0,
+                    col_start: 0,
+                    col_end: 2,
+                },
+            },
+        ),
     ];
 
     for (text, result) in &params {
         println!("Using regex: {}", *text);
-        assert_eq!(match_token(*text, 0, 0).unwrap(), *result);
+        assert_eq!(match_token(text, 0, 0).unwrap(), *result);
     }
 }
 
@@ -102,3 +125,32 @@ fn test_match_line() {
     print!("{:#?}", m);
     assert_eq!(expected, *m);
 }
+
+#[test]
+fn test_tokenize_end_of_line() {
+    let lines = ["10 PRINT 1", "", "REM only a comment", "20 GOTO 10"];
+
+    let tokens = tokenize(&lines).unwrap();
+    let kinds: Vec<_> = tokens.iter().map(|t| &t.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            &TokenType::Number(10),
+            &TokenType::Print,
+            &TokenType::Number(1),
+            &TokenType::EndOfLine,
+            &TokenType::Number(20),
+            &TokenType::Goto,
+            &TokenType::Number(10),
+            &TokenType::EndOfLine,
+        ]
+    );
+    assert_eq!(
+        tokens[7].position,
+        Position {
+            line_num: 3,
+            col_start: 10,
+            col_end: 10,
+        }
+    );
+}
@@ -0,0 +1,473 @@
+use super::expressions::Expression;
+use super::statements::Statement;
+use super::tokenizer::Position;
+use super::{Line, ParseError, Result, StatementIndex};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// How lines that are not in ascending order of their line numbers are treated
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineOrder {
+    /// Sort the lines by their number, like classic BASIC does when a listing is typed in
+    #[default]
+    Sort,
+    /// Report a `ParseError::UnsortedLineNumber`
+    Reject,
+}
+
+/// Checks the structure of a parsed program: line numbers have to be
+/// unique, unsorted lines are sorted or rejected depending on `line_order`.
+/// The literal targets of ON GOTO and ON GOSUB have to exist. Afterwards the
+/// jump targets of ELSE branches, IF blocks and loops are resolved, which
+/// fails with `ParseError::SharedStatement` for statements the caller shares.
+pub fn validate_lines(mut lines: Vec<Line>, line_order: LineOrder) -> Result<Vec<Line>> {
+    let mut first_positions = HashMap::with_capacity(lines.len());
+    for line in &lines {
+        if let Some(first) = first_positions.insert(line.line_id, line.position) {
+            return Err(ParseError::DuplicateLineNumber {
+                line_id: line.line_id,
+                first,
+                second: line.position,
+            });
+        }
+    }
+
+    if let Some(pair) = lines
+        .windows(2)
+        .find(|pair| pair[0].line_id > pair[1].line_id)
+    {
+        match line_order {
+            LineOrder::Sort => lines.sort_by_key(|line| line.line_id),
+            LineOrder::Reject => {
+                return Err(ParseError::UnsortedLineNumber {
+                    line_id: pair[1].line_id,
+                    previous_line_id: pair[0].line_id,
+                    position: pair[1].position,
+                });
+            }
+        }
+    }
+
+    check_jump_targets(&lines)?;
+    resolve_blocks(&mut lines)?;
+    Ok(lines)
+}
+
+/// Checks that the line numbers written out as targets of ON GOTO, ON GOSUB
+/// and ON ERROR GOTO exist, computed targets are checked when the jump happens
+fn check_jump_targets(lines: &[Line]) -> Result<()> {
+    let line_ids = lines
+        .iter()
+        .map(|line| line.line_id)
+        .collect::<HashSet<_>>();
+    let statements = lines.iter().flat_map(|line| {
+        line.statements
+            .iter()
+            .flat_map(|statement| statement.content.with_then_statements())
+    });
+
+    for statement in statements {
+        let targets = match statement {
+            Statement::On(on_statement) => on_statement.targets.iter().collect(),
+            Statement::OnError(target) => vec![&**target],
+            _ => continue,
+        };
+        for target in targets {
+            let Expression::NumberLiteral(line_id) = target.content else {
+                continue;
+            };
+            // ON ERROR GOTO 0 removes the handler
+            let removes_handler = line_id == 0 && matches!(statement, Statement::OnError(_));
+            let exists = usize::try_from(line_id).is_ok_and(|line_id| line_ids.contains(&line_id));
+            if !exists && !removes_handler {
+                return Err(ParseError::UndefinedLine {
+                    line_id,
+                    position: target.position,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Kind of a block which is not closed yet
+enum BlockKind {
+    If {
+        /// IF or ELSEIF whose `else_target` is the next branch, `None` after ELSE
+        open_condition: Option<StatementIndex>,
+        /// ELSEIF and ELSE statements, which continue at the END IF
+        branches: Vec<StatementIndex>,
+    },
+    While,
+    Do,
+}
+
+/// A block which is not closed yet, `index` is the statement opening it
+struct Block {
+    kind: BlockKind,
+    index: StatementIndex,
+    position: Position,
+}
+
+impl Block {
+    fn statement(&self) -> &'static str {
+        match self.kind {
+            BlockKind::If { .. } => "IF",
+            BlockKind::While => "WHILE",
+            BlockKind::Do => "DO",
+        }
+    }
+
+    fn end_statement(&self) -> &'static str {
+        match self.kind {
+            BlockKind::If { .. } => "END IF",
+            BlockKind::While => "WEND",
+            BlockKind::Do => "LOOP",
+        }
+    }
+}
+
+/// Name of a statement which starts or ends a block and of its counterpart
+fn block_statement(statement: &Statement) -> Option<(&'static str, &'static str)> {
+    match statement {
+        Statement::If(if_stmt) if if_stmt.then_statement.is_none() => Some(("IF", "END IF")),
+        Statement::ElseIf(_) => Some(("ELSEIF", "IF")),
+        Statement::Else(_) => Some(("ELSE", "IF")),
+        Statement::EndIf => Some(("END IF", "IF")),
+        Statement::While(_) => Some(("WHILE", "WEND")),
+        Statement::Wend(_) => Some(("WEND", "WHILE")),
+        Statement::Do(_) => Some(("DO", "LOOP")),
+        Statement::Loop(_) => Some(("LOOP", "DO")),
+        _ => None,
+    }
+}
+
+/// Statements are changed in place, which fails if the caller shares them
+fn statement_mut(lines: &mut [Line], index: StatementIndex) -> Result<&mut Statement> {
+    let statement = &mut lines[index.line].statements[index.statement];
+    let position = statement.position;
+    Rc::get_mut(statement)
+        .map(|statement| &mut statement.content)
+        .ok_or(ParseError::SharedStatement { position })
+}
+
+/// Sets the `else_target` of an IF or ELSEIF, `depth` counts the IFs nested
+/// as THEN statements: 'IF a THEN IF b THEN ...'
+fn set_else_target(
+    lines: &mut [Line],
+    index: StatementIndex,
+    depth: usize,
+    target: StatementIndex,
+) -> Result<()> {
+    let mut statement = statement_mut(lines, index)?;
+    for _ in 0..depth {
+        let Statement::If(if_stmt) = statement else {
+            unreachable!("Only IFs are nested");
+        };
+        statement = &mut if_stmt
+            .then_statement
+            .as_mut()
+            .expect("Nested IFs have a THEN statement")
+            .content;
+    }
+
+    match statement {
+        Statement::If(if_stmt) => if_stmt.else_target = Some(target),
+        Statement::ElseIf(else_if) => else_if.else_target = Some(target),
+        _ => unreachable!("Only IF and ELSEIF have an else target"),
+    }
+    Ok(())
+}
+
+fn unmatched(statement: &'static str, expected: &'static str, position: Position) -> ParseError {
+    ParseError::UnmatchedBlockStatement {
+        statement,
+        expected,
+        position,
+    }
+}
+
+fn mismatched(block: &Block, statement: &'static str, position: Position) -> ParseError {
+    ParseError::MismatchedBlock {
+        statement,
+        position,
+        open_statement: block.statement(),
+        open_position: block.position,
+        expected: block.end_statement(),
+    }
+}
+
+/// Removes the innermost block, which has to be started by `start` to be
+/// closed by `statement`
+fn close_block(
+    blocks: &mut Vec<Block>,
+    statement: &'static str,
+    start: &'static str,
+    position: Position,
+) -> Result<Block> {
+    let Some(block) = blocks.pop() else {
+        return Err(unmatched(statement, start, position));
+    };
+    if block.statement() != start {
+        return Err(mismatched(&block, statement, position));
+    }
+    Ok(block)
+}
+
+/// The open condition and the branches of the innermost block, which has to
+/// be a block IF for an ELSE or ELSEIF
+fn innermost_if<'b>(
+    blocks: &'b mut [Block],
+    statement: &'static str,
+    position: Position,
+) -> Result<(&'b mut Option<StatementIndex>, &'b mut Vec<StatementIndex>)> {
+    match blocks.last_mut() {
+        None => Err(unmatched(statement, "IF", position)),
+        Some(Block {
+            kind:
+                BlockKind::If {
+                    open_condition,
+                    branches,
+                },
+            ..
+        }) => Ok((open_condition, branches)),
+        Some(block) => Err(mismatched(block, statement, position)),
+    }
+}
+
+/// Matches the blocks of IF, ELSEIF, ELSE and END IF, of WHILE and WEND and
+/// of DO and LOOP, as well as an ELSE with the IF on its line. The statements
+/// store the matches as targets where the execution continues.
+fn resolve_blocks(lines: &mut [Line]) -> Result<()> {
+    let mut blocks: Vec<Block> = Vec::new();
+
+    for line in 0..lines.len() {
+        // Single line IFs waiting for their ELSE: statement and nesting depth.
+        // Like in classic BASIC an ELSE belongs to the innermost IF.
+        let mut single_line_ifs: Vec<(usize, usize)> = Vec::new();
+
+        for statement_index in 0..lines[line].statements.len() {
+            let index = StatementIndex {
+                line,
+                statement: statement_index,
+            };
+
+            let mut statement = &*lines[line].statements[statement_index];
+            let mut depth = 0;
+            while let Statement::If(if_stmt) = &statement.content
+                && let Some(then_statement) = &if_stmt.then_statement
+            {
+                single_line_ifs.push((statement_index, depth));
+                statement = then_statement;
+                depth += 1;
+            }
+            let position = statement.position;
+
+            // A block can not start or end after THEN
+            if depth > 0
+                && let Some((name, counterpart)) = block_statement(&statement.content)
+            {
+                return Err(unmatched(name, counterpart, position));
+            }
+
+            match &statement.content {
+                Statement::If(_) => blocks.push(Block {
+                    kind: BlockKind::If {
+                        open_condition: Some(index),
+                        branches: Vec::new(),
+                    },
+                    index,
+                    position,
+                }),
+                Statement::Else(_) => {
+                    if let Some((if_index, if_depth)) = single_line_ifs.pop() {
+                        let if_index = StatementIndex {
+                            line,
+                            statement: if_index,
+                        };
+                        set_else_target(lines, if_index, if_depth, index)?;
+                    } else {
+                        let (open_condition, branches) =
+                            innermost_if(&mut blocks, "ELSE", position)?;
+                        let Some(condition) = open_condition.take() else {
+                            return Err(unmatched("ELSE", "IF", position));
+                        };
+                        branches.push(index);
+                        set_else_target(lines, condition, 0, index)?;
+                    }
+                }
+                Statement::ElseIf(_) => {
+                    let (open_condition, branches) = innermost_if(&mut blocks, "ELSEIF", position)?;
+                    let Some(condition) = open_condition.replace(index) else {
+                        return Err(unmatched("ELSEIF", "IF", position));
+                    };
+                    branches.push(index);
+                    set_else_target(lines, condition, 0, index)?;
+                }
+                Statement::EndIf => {
+                    let block = close_block(&mut blocks, "END IF", "IF", position)?;
+                    let BlockKind::If {
+                        open_condition,
+                        branches,
+                    } = block.kind
+                    else {
+                        unreachable!("Checked by close_block");
+                    };
+                    if let Some(condition) = open_condition {
+                        set_else_target(lines, condition, 0, index)?;
+                    }
+                    for branch in branches {
+                        match statement_mut(lines, branch)? {
+                            Statement::ElseIf(else_if) => else_if.end_target = Some(index),
+                            Statement::Else(end_target) => *end_target = Some(index),
+                            _ => unreachable!("Branches are ELSEIF or ELSE"),
+                        }
+                    }
+                }
+                Statement::While(_) => blocks.push(Block {
+                    kind: BlockKind::While,
+                    index,
+                    position,
+                }),
+                Statement::Wend(_) => {
+                    let block = close_block(&mut blocks, "WEND", "WHILE", position)?;
+                    if let Statement::While(while_stmt) = statement_mut(lines, block.index)? {
+                        while_stmt.end_target = Some(index);
+                    }
+                    if let Statement::Wend(start_target) = statement_mut(lines, index)? {
+                        *start_target = Some(block.index);
+                    }
+                }
+                Statement::Do(_) => blocks.push(Block {
+                    kind: BlockKind::Do,
+                    index,
+                    position,
+                }),
+                Statement::Loop(_) => {
+                    let block = close_block(&mut blocks, "LOOP", "DO", position)?;
+                    if let Statement::Do(do_stmt) = statement_mut(lines, block.index)? {
+                        do_stmt.end_target = Some(index);
+                    }
+                    if let Statement::Loop(loop_stmt) = statement_mut(lines, index)? {
+                        loop_stmt.start_target = Some(block.index);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    if let Some(block) = blocks.pop() {
+        return Err(ParseError::UnterminatedBlock {
+            statement: block.statement(),
+            expected: block.end_statement(),
+            position: block.position,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineOrder, validate_lines};
+    use crate::parser::statements::Statement;
+    use crate::parser::tokenizer::tokenize;
+    use crate::parser::{Line, ParseError, StatementIndex, parse_tokens};
+    use std::rc::Rc;
+
+    fn parse_unchecked(program: &[&str]) -> Vec<Line> {
+        let tokens = tokenize(program).unwrap();
+        let mut iter_token = tokens.iter().peekable();
+        let mut lines = Vec::new();
+        while iter_token.peek().is_some() {
+            lines.push(Line::parse(&mut iter_token).unwrap());
+        }
+        lines
+    }
+
+    #[test]
+    fn test_duplicate_line_number() {
+        let lines = parse_unchecked(&["10 PRINT 1", "20 PRINT 2", "10 PRINT 3"]);
+        let result = validate_lines(lines, LineOrder::Sort);
+        let Err(ParseError::DuplicateLineNumber {
+            line_id,
+            first,
+            second,
+        }) = result
+        else {
+            panic!("Unexpected result: {result:?}");
+        };
+        assert_eq!(line_id, 10);
+        assert_eq!((first.line_num, second.line_num), (0, 2));
+    }
+
+    #[test]
+    fn test_unsorted_lines() {
+        let lines = parse_unchecked(&["20 PRINT 1", "10 PRINT 2"]);
+        let result = validate_lines(lines, LineOrder::Reject);
+        assert!(matches!(
+            result,
+            Err(ParseError::UnsortedLineNumber { line_id: 10, .. })
+        ));
+
+        let tokens = tokenize(&["30 PRINT 1", "10 PRINT 2", "20 PRINT 3"]).unwrap();
+        let lines = parse_tokens(&tokens).unwrap();
+        let ids: Vec<usize> = lines.iter().map(|line| line.line_id).collect();
+        assert_eq!(ids, [10, 20, 30]);
+    }
+
+    #[test]
+    fn test_undefined_jump_target() {
+        let tokens = tokenize(&["10 ON A GOTO 10, 30", "20 ON A GOSUB 10, 20 + 10"]).unwrap();
+        let result = parse_tokens(&tokens);
+        let Err(ParseError::UndefinedLine { line_id, position }) = result else {
+            panic!("Unexpected result: {result:?}");
+        };
+        assert_eq!(line_id, 30);
+        assert_eq!((position.line_num, position.col_start), (0, 17));
+
+        let tokens = tokenize(&["10 IF A = 1 THEN ON A GOTO 20", "20 PRINT A"]).unwrap();
+        assert!(parse_tokens(&tokens).is_ok());
+        let tokens = tokenize(&["10 IF A = 1 THEN ON A GOTO 30", "20 PRINT A"]).unwrap();
+        assert!(parse_tokens(&tokens).is_err());
+    }
+
+    #[test]
+    fn test_resolve_blocks() {
+        let tokens = tokenize(&[
+            "10 IF A = 1 THEN PRINT 1 ELSE PRINT 2",
+            "20 IF A = 2 THEN",
+            "30 ELSE",
+            "40 END IF",
+        ])
+        .unwrap();
+        let lines = parse_tokens(&tokens).unwrap();
+
+        let at = |line, statement| StatementIndex { line, statement };
+        let Statement::If(single_line_if) = &lines[0].statements[0].content else {
+            panic!("Expected IF");
+        };
+        assert_eq!(single_line_if.else_target, Some(at(0, 1)));
+        assert_eq!(lines[0].statements[1].content, Statement::Else(None));
+
+        let Statement::If(block_if) = &lines[1].statements[0].content else {
+            panic!("Expected IF");
+        };
+        assert_eq!(block_if.else_target, Some(at(2, 0)));
+        assert_eq!(
+            lines[2].statements[0].content,
+            Statement::Else(Some(at(3, 0)))
+        );
+    }
+
+    #[test]
+    fn test_shared_statement() {
+        let lines = parse_unchecked(&["10 WHILE A < 3", "20 WEND"]);
+        let shared = Rc::clone(&lines[1].statements[0]);
+        let result = validate_lines(lines, LineOrder::Sort);
+        let Err(ParseError::SharedStatement { position }) = result else {
+            panic!("Unexpected result: {result:?}");
+        };
+        assert_eq!(position, shared.position);
+    }
+}
@@ -1,5 +1,5 @@
 use super::tokenizer::tokenize;
-use super::{Line, Result};
+use super::{Line, ParseError, Result, parse_tokens};
 #[test]
 fn test_lines() -> Result<()> {
     // -- Read input
@@ -8,13 +8,14 @@ fn test_lines() -> Result<()> {
         vec!["20 GOTO 20+B".to_string()],
         vec!["30 GOTOSUB 40".to_string()],
         vec!["40 IF B<>33 THEN GOTO 42".to_string()],
+        vec!["50 LET A = 1 : PRINT A : GOSUB 100".to_string()],
     ];
 
     for line in &lines {
         println!("{:#?}", line);
 
         println!("* Tokenizing");
-        let tokens = tokenize(&line)?;
+        let tokens = tokenize(line)?;
 
         println!("* Parsing");
         let mut iter_token = tokens.iter().peekable();
@@ -23,3 +24,36 @@ fn test_lines() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_trailing_tokens() {
+    let params = [
+        // Two statements on one physical line
+        (vec!["10 PRINT 1 20 PRINT 2"], "Number(20)", 11),
+        // Trailing junk after a complete statement
+        (vec!["10 GOTO 20 30"], "Number(30)", 11),
+        (vec!["10 LET A = 1 )"], "CloseParen", 13),
+    ];
+
+    for (lines, expected_token, expected_col) in params {
+        let tokens = tokenize(&lines).unwrap();
+        let result = parse_tokens(&tokens);
+        println!("{result:?}");
+        let Err(ParseError::TrailingToken { token, position }) = result else {
+            panic!("Expected trailing token error for {lines:?}");
+        };
+        assert_eq!(token, expected_token);
+        assert_eq!(position.col_start, expected_col);
+    }
+}
+
+#[test]
+fn test_line_end_stops_expression() {
+    // The expression must not continue with the next line number
+    let lines = ["10 GOTO 20 +", "20 PRINT 1"];
+
+    let tokens = tokenize(&lines).unwrap();
+    let result = parse_tokens(&tokens);
+    println!("{result:?}");
+    assert!(matches!(result, Err(ParseError::WrongToken { .. })));
+}
@@ -4,14 +4,29 @@ use anyhow::bail;
 use glob::glob;
 use nanobasic::interpreter::Interpreter;
 use nanobasic::interpreter::InterpreterError;
+use nanobasic::interpreter::ProgramState;
 use nanobasic::parser;
-use nanobasic::parser::tokenizer::{Token, tokenize};
+use nanobasic::parser::ParseError;
+use nanobasic::parser::ParseOptions;
+use nanobasic::parser::tokenizer::{KeywordMode, Token, tokenize};
+use nanobasic::parser::validation::LineOrder;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 const TEST_DIR: &str = "Examples";
 
+fn run_program(program: &str) -> Result<String> {
+    run_program_with_input(program, "")
+}
+
+fn run_program_with_input(program: &str, input: &str) -> Result<String> {
+    let mut output = Vec::<u8>::new();
+    let mut interpreter = Interpreter::from_str(program)?;
+    interpreter.run(&mut input.as_bytes(), &mut output)?;
+    Ok(String::from_utf8(output)?)
+}
+
 fn tokenize_from_file(path: impl AsRef<Path>) -> Result<Vec<Token>> {
     let path = path.as_ref();
     log::info!(r#"Parsing tokens from "{path:#?}""#);
@@ -64,9 +79,11 @@ pub fn test_interpret_all_examples() {
         let lines = parser::parse_file(&path).unwrap();
         let mut stdout = std::io::stdout();
         let mut nano_interpreter = Interpreter::from_ast(lines);
-        nano_interpreter.run(&mut stdout).unwrap();
+        nano_interpreter
+            .run(&mut std::io::empty(), &mut stdout)
+            .unwrap();
         println!("✅ -------------------------------------------------------------");
-        println!("")
+        println!()
     }
 }
 
@@ -82,10 +99,11 @@ pub fn test_error_on_finished() -> Result<()> {
     let mut interpreter = Interpreter::from_str(program)?;
 
     for _ in 1..10 {
-        if let Err(InterpreterError::Finished) = interpreter.step_line(&mut stdout) {
-            if interpreter.current_line() != 30 {
-                bail!("Wrong line number!")
-            }
+        if let Err(InterpreterError::Finished) =
+            interpreter.step_line(&mut std::io::empty(), &mut stdout)
+            && interpreter.current_line() != 30
+        {
+            bail!("Wrong line number!")
         }
     }
     Ok(())
@@ -103,10 +121,1021 @@ pub fn test_step_line() -> Result<()> {
     let mut interpreter = Interpreter::from_str(program)?;
 
     while !interpreter.finished() {
-        interpreter.step_line(&mut stdout)?
+        interpreter.step_line(&mut std::io::empty(), &mut stdout)?
     }
     if interpreter.current_line() != 30 {
         bail!("wrong line number");
     }
     Ok(())
 }
+
+#[test]
+pub fn test_for_next() -> Result<()> {
+    let program = r#"
+        10 FOR I = 1 TO 3
+        20 PRINT I
+        30 NEXT I
+        40 FOR I = 6 TO 1 STEP -2
+        50 FOR J = 1 TO 2
+        60 PRINT I, J
+        70 NEXT
+        80 NEXT I
+    "#;
+
+    let output = run_program(program)?;
+    assert_eq!(output, "1\n2\n3\n6\t1\n6\t2\n4\t1\n4\t2\n2\t1\n2\t2\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_for_runs_body_once() -> Result<()> {
+    // The C64 checks the limit only at NEXT, GW-BASIC would print nothing
+    let program = r#"
+        10 FOR I = 1 TO 0
+        20 PRINT I
+        30 NEXT I
+        40 FOR J = 0 TO 5 STEP -1
+        50 PRINT J
+        60 NEXT J
+        70 PRINT I, J
+    "#;
+    let output = run_program(program)?;
+    assert_eq!(output, "1\n0\n2\t-1\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_next_errors() {
+    let result = run_program("10 NEXT I");
+    assert!(matches!(
+        result.unwrap_err().downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::NextWithoutFor)
+    ));
+
+    let result = run_program("10 FOR I = 1 TO 2\n20 NEXT J");
+    assert!(matches!(
+        result.unwrap_err().downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::MismatchedNext { .. })
+    ));
+}
+
+#[test]
+pub fn test_arrays() -> Result<()> {
+    let program = r#"
+        10 DIM A(3), B(2, 2)
+        20 FOR I = 0 TO 3
+        30 LET A(I) = I * I
+        40 NEXT I
+        50 LET B(1, 2) = A(3) + 1
+        60 PRINT A(2), B(1, 2), B(0, 0)
+    "#;
+
+    let output = run_program(program)?;
+    assert_eq!(output, "4\t10\t0\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_array_errors() {
+    let result = run_program("10 DIM A(3)\n20 PRINT A(4)");
+    let err = result.unwrap_err();
+    let Some(InterpreterError::IndexOutOfRange {
+        index, position, ..
+    }) = err.downcast_ref::<InterpreterError>()
+    else {
+        panic!("Unexpected error: {err:?}");
+    };
+    assert_eq!(*index, 4);
+    assert_eq!((position.line_num, position.col_start), (1, 11));
+
+    let result = run_program("10 LET B(1) = 2");
+    assert!(matches!(
+        result.unwrap_err().downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::UndimensionedArray { .. })
+    ));
+
+    for program in ["10 DIM A(100000000000)", "10 DIM A(3037000500, 3037000500)"] {
+        let err = run_program(program).unwrap_err();
+        let Some(InterpreterError::ArrayTooLarge { name, position }) =
+            err.downcast_ref::<InterpreterError>()
+        else {
+            panic!("Unexpected error: {err:?}");
+        };
+        assert_eq!(name, "A");
+        assert_eq!(position.col_start, 7, "{program}");
+    }
+}
+
+#[test]
+pub fn test_strings() -> Result<()> {
+    let program = r#"
+        10 LET A$ = "Nano"
+        20 DIM B$(1)
+        30 LET B$(1) = A$ + "BASIC"
+        40 LET C$ = "abd"
+        50 PRINT B$(1), B$(0) + A$
+        60 IF A$ = "Nano" THEN PRINT A$
+        70 IF "abc" < C$ THEN PRINT C$
+    "#;
+
+    let output = run_program(program)?;
+    assert_eq!(output, "NanoBASIC\tNano\nNano\nabd\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_type_mismatch() {
+    for (program, expected_col) in [
+        ("10 LET A$ = 5", 12),
+        ("10 LET A = \"five\"", 11),
+        ("10 PRINT \"A\" + 1", 15),
+        ("10 IF 1 = \"1\" THEN PRINT 1", 10),
+    ] {
+        let err = run_program(program).unwrap_err();
+        let Some(InterpreterError::TypeMismatch { position, .. }) =
+            err.downcast_ref::<InterpreterError>()
+        else {
+            panic!("Unexpected error: {err:?}");
+        };
+        assert_eq!(position.col_start, expected_col, "{program}");
+    }
+}
+
+#[test]
+pub fn test_input() -> Result<()> {
+    let program = r#"
+        10 DIM A(1)
+        20 INPUT "Name"; N$
+        30 INPUT A(0), A(1)
+        40 PRINT N$, A(0) + A(1)
+    "#;
+
+    let output = run_program_with_input(program, "Ada\nx,1\n1\n2\n")?;
+    assert_eq!(output, "Name? ? ?REDO FROM START\n? ?? Ada\t3\n");
+
+    let err = run_program_with_input(program, "Ada\n").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::EndOfInput)
+    ));
+    Ok(())
+}
+
+#[test]
+pub fn test_floats() -> Result<()> {
+    let program = r#"
+        10 PRINT 7 / 2, 8 / 2, 1.5 * 2, 0.1 + 0.2
+        20 PRINT 1E-3, 2.5E20, -.5
+        30 IF 2 = 2.0 THEN PRINT 2 * 3.5
+        40 FOR X = 1 TO 0 STEP -0.5
+        50 PRINT X
+        60 NEXT X
+    "#;
+    let output = run_program(program)?;
+    assert_eq!(
+        output,
+        "3.5\t4\t3\t0.3\n0.001\t2.5E+20\t-0.5\n7\n1\n0.5\n0\n"
+    );
+    Ok(())
+}
+
+#[test]
+pub fn test_negative_zero() -> Result<()> {
+    let program = r#"
+        10 LET X = -1.5 * 0
+        20 IF X = 0 THEN PRINT "zero"
+        30 PRINT X, SGN(X), SGN(-X)
+        40 IF X < 0 OR -X < 0 THEN PRINT "negative"
+    "#;
+    let output = run_program(program)?;
+    assert_eq!(output, "zero\n0\t0\t0\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_arithmetic_errors() {
+    let err = run_program("10 PRINT 1\n20 PRINT 5 + 1 / 0").unwrap_err();
+    let Some(InterpreterError::DivisionByZero { line_id, position }) =
+        err.downcast_ref::<InterpreterError>()
+    else {
+        panic!("Unexpected error: {err:?}");
+    };
+    assert_eq!(*line_id, 20);
+    assert_eq!((position.col_start, position.col_end), (13, 18));
+
+    for program in ["10 PRINT 2.5 / 0.0", "10 LET A = 0\n20 PRINT 7 / A"] {
+        let err = run_program(program).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::DivisionByZero { .. })
+        ));
+    }
+
+    for program in [
+        "10 LET A = 9223372036854775807\n20 LET A = A + 1",
+        "10 LET A = 3037000500 * 3037000500",
+        "10 LET A = 1E300 * 1E300",
+        "10 PRINT LEFT$(\"abc\", 1E20)",
+        "10 PRINT VAL(\"1e400\")",
+        "10 GOTO 1E30",
+        "10 DIM A(1E30)",
+    ] {
+        let err = run_program(program).unwrap_err();
+        assert!(
+            matches!(
+                err.downcast_ref::<InterpreterError>(),
+                Some(InterpreterError::Overflow { .. })
+            ),
+            "{program}: {err:?}"
+        );
+    }
+}
+
+#[test]
+pub fn test_multiple_statements() -> Result<()> {
+    let program = r#"
+        10 LET A = 1 : LET B = 2 : PRINT A + B
+        20 FOR I = 1 TO 3 : PRINT I : NEXT I
+        30 GOSUB 100 : PRINT "back"
+        40 IF A = 1 THEN PRINT "one" : PRINT A + 10
+        50 IF A = 2 THEN PRINT "two" : PRINT A + 20
+        60 GOTO 200
+        100 PRINT "sub" : RETURN
+        200 PRINT "end"
+    "#;
+
+    let output = run_program(program)?;
+    assert_eq!(output, "3\n1\n2\n3\nsub\nback\none\n11\nend\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_step_line_with_multiple_statements() -> Result<()> {
+    let program = "10 PRINT 1 : PRINT 2 : GOTO 30 : PRINT 3\n20 PRINT 4\n30 PRINT 5";
+
+    let mut output = Vec::<u8>::new();
+    let mut interpreter = Interpreter::from_str(program)?;
+
+    // A line runs up to its end or up to the first jump
+    interpreter.step_line(&mut std::io::empty(), &mut output)?;
+    assert_eq!(String::from_utf8(output.clone())?, "1\n2\n");
+    interpreter.step_line(&mut std::io::empty(), &mut output)?;
+    assert_eq!(interpreter.current_line(), 30);
+    assert!(interpreter.finished());
+    Ok(())
+}
+
+#[test]
+pub fn test_else() -> Result<()> {
+    let program = r#"
+        10 FOR I = 1 TO 2
+        20 IF I = 1 THEN PRINT 1 : PRINT 11 ELSE PRINT 2 : PRINT 22
+        30 IF I = 2 THEN IF I > 5 THEN PRINT 3 ELSE PRINT 4 ELSE PRINT 5
+        40 NEXT I
+    "#;
+
+    let output = run_program(program)?;
+    assert_eq!(output, "1\n11\n5\n2\n22\n4\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_else_without_statement() {
+    for program in [
+        "10 IF 1 = 1 THEN PRINT 1 ELSE",
+        "10 IF 1 = 1 THEN PRINT 1 ELSE\n20 PRINT 2",
+    ] {
+        let err = run_program(program).unwrap_err();
+        let Some(InterpreterError::ParseErrorError(ParseError::MissingStatement {
+            statement: "ELSE",
+            position,
+        })) = err.downcast_ref::<InterpreterError>()
+        else {
+            panic!("Unexpected error for {program}: {err:?}");
+        };
+        assert_eq!(
+            (position.line_num, position.col_start),
+            (0, 25),
+            "{program}"
+        );
+    }
+}
+
+#[test]
+pub fn test_block_if() -> Result<()> {
+    let program = r#"
+        10 FOR I = 1 TO 4
+        20 IF I = 1 THEN
+        30   PRINT 10
+        40 ELSEIF I = 2 THEN
+        50   PRINT 20
+        60   IF I > 0 THEN
+        70     PRINT 21
+        80   END IF
+        90 ELSEIF I = 3 THEN
+        100   PRINT 30
+        110 ELSE
+        120   PRINT 40
+        130 END IF
+        140 NEXT I
+        150 IF I < 0 THEN
+        160   PRINT 50
+        170 END IF
+    "#;
+
+    let output = run_program(program)?;
+    assert_eq!(output, "10\n20\n21\n30\n40\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_unterminated_block_if() {
+    let err = run_program("10 IF 1 = 1 THEN\n20 PRINT 1").unwrap_err();
+    let Some(InterpreterError::ParseErrorError(ParseError::UnterminatedBlock { position, .. })) =
+        err.downcast_ref::<InterpreterError>()
+    else {
+        panic!("Unexpected error: {err:?}");
+    };
+    assert_eq!(position.line_num, 0);
+
+    for program in [
+        "10 END IF",
+        "10 PRINT 1 ELSE PRINT 2",
+        "10 IF 1 = 1 THEN\n20 ELSE\n30 ELSE\n40 END IF",
+    ] {
+        let err = run_program(program).unwrap_err();
+        assert!(
+            matches!(
+                err.downcast_ref::<InterpreterError>(),
+                Some(InterpreterError::ParseErrorError(
+                    ParseError::UnmatchedBlockStatement { .. }
+                ))
+            ),
+            "{program}: {err:?}"
+        );
+    }
+}
+
+#[test]
+pub fn test_logical_operators() -> Result<()> {
+    let program = r#"
+        10 FOR X = 0 TO 12 STEP 4
+        20 IF X > 0 AND X < 10 THEN PRINT X
+        30 IF NOT (X > 0 AND X < 10) OR X = 4 THEN PRINT -X
+        40 NEXT X
+        50 LET A = 0
+        60 IF A <> 0 AND 10 / A > 1 THEN PRINT 1
+        70 IF A = 0 OR 10 / A > 1 THEN PRINT 2
+    "#;
+
+    let output = run_program(program)?;
+    assert_eq!(output, "0\n4\n-4\n8\n-12\n2\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_while_wend() -> Result<()> {
+    let program = r#"
+        10 LET I = 0
+        20 WHILE I < 3
+        30   LET J = 0
+        40   WHILE J < I : LET J = J + 1 : PRINT I, J : WEND
+        50   LET I = I + 1
+        60 WEND
+        70 WHILE I > 10
+        80   PRINT -1
+        90 WEND
+    "#;
+
+    let output = run_program(program)?;
+    assert_eq!(output, "1\t1\n2\t1\n2\t2\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_do_loop() -> Result<()> {
+    let program = r#"
+        10 LET I = 5
+        20 DO WHILE I < 3
+        30   PRINT -1
+        40 LOOP
+        50 DO
+        60   PRINT I
+        70   LET I = I + 1
+        80 LOOP UNTIL I >= 3
+        90 DO UNTIL I = 8
+        100   LET I = I + 1
+        110 LOOP WHILE I < 7
+        120 PRINT I
+    "#;
+
+    let output = run_program(program)?;
+    assert_eq!(output, "5\n7\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_unbalanced_loops() {
+    let err = run_program("10 WHILE 1 = 1\n20 DO\n30 WEND\n40 LOOP").unwrap_err();
+    let Some(InterpreterError::ParseErrorError(ParseError::MismatchedBlock {
+        statement,
+        position,
+        open_statement,
+        open_position,
+        ..
+    })) = err.downcast_ref::<InterpreterError>()
+    else {
+        panic!("Unexpected error: {err:?}");
+    };
+    assert_eq!((*statement, *open_statement), ("WEND", "DO"));
+    assert_eq!((position.line_num, open_position.line_num), (2, 1));
+
+    let err = run_program("10 DO\n20 PRINT 1").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::ParseErrorError(
+            ParseError::UnterminatedBlock {
+                statement: "DO",
+                ..
+            }
+        ))
+    ));
+
+    let err = run_program("10 LOOP").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::ParseErrorError(
+            ParseError::UnmatchedBlockStatement { .. }
+        ))
+    ));
+}
+
+#[test]
+pub fn test_end() -> Result<()> {
+    let program = r#"
+        10 GOSUB 100
+        20 PRINT 2
+        30 END
+        100 PRINT 1
+        110 RETURN
+    "#;
+
+    let mut output = Vec::<u8>::new();
+    let mut interpreter = Interpreter::from_str(program)?;
+    interpreter.run(&mut std::io::empty(), &mut output)?;
+    assert_eq!(String::from_utf8(output)?, "1\n2\n");
+    assert_eq!(interpreter.state(), ProgramState::Finished);
+    assert!(matches!(
+        interpreter.cont(),
+        Err(InterpreterError::CannotContinue)
+    ));
+    Ok(())
+}
+
+#[test]
+pub fn test_stop_and_cont() -> Result<()> {
+    let program = "10 PRINT 1 : STOP : PRINT 2\n20 STOP\n30 PRINT 3";
+
+    let mut output = Vec::<u8>::new();
+    let mut interpreter = Interpreter::from_str(program)?;
+    interpreter.run(&mut std::io::empty(), &mut output)?;
+    assert_eq!(interpreter.state(), ProgramState::Stopped { line_id: 10 });
+    assert!(interpreter.finished());
+    assert!(matches!(
+        interpreter.step_line(&mut std::io::empty(), &mut output),
+        Err(InterpreterError::Stopped { line_id: 10 })
+    ));
+
+    interpreter.cont()?;
+    interpreter.run(&mut std::io::empty(), &mut output)?;
+    assert_eq!(interpreter.state(), ProgramState::Stopped { line_id: 20 });
+
+    interpreter.cont()?;
+    interpreter.run(&mut std::io::empty(), &mut output)?;
+    assert_eq!(interpreter.state(), ProgramState::Finished);
+    assert_eq!(String::from_utf8(output)?, "1\n2\n3\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_errored_state() -> Result<()> {
+    let mut output = Vec::<u8>::new();
+    let mut interpreter = Interpreter::from_str("10 PRINT 1\n20 PRINT 1 / 0")?;
+    assert!(interpreter.run(&mut std::io::empty(), &mut output).is_err());
+    assert_eq!(interpreter.state(), ProgramState::Errored { line_id: 20 });
+    assert!(matches!(
+        interpreter.step_line(&mut std::io::empty(), &mut output),
+        Err(InterpreterError::Finished)
+    ));
+    Ok(())
+}
+
+#[test]
+pub fn test_builtin_functions() -> Result<()> {
+    let params = [
+        ("ABS(-3)", "3"),
+        ("abs(2.5)", "2.5"),
+        ("SGN(-0.5)", "-1"),
+        ("SGN(0) + SGN(7)", "1"),
+        ("INT(-2.5)", "-3"),
+        ("INT(7 / 2)", "3"),
+        ("SQR(16)", "4"),
+        ("MOD(7, 3)", "1"),
+        ("MOD(-7, 3)", "-1"),
+        ("MIN(4, 2, 8)", "2"),
+        ("MAX(4, 2.5, -8)", "4"),
+        ("ABS(MIN(-1, -5)) * 2", "10"),
+    ];
+
+    for (expression, expected) in params {
+        let output = run_program(&format!("10 PRINT {expression}"))?;
+        assert_eq!(output, format!("{expected}\n"), "{expression}");
+    }
+    Ok(())
+}
+
+#[test]
+pub fn test_builtin_arity() -> Result<()> {
+    let params = [
+        "10 PRINT ABS(1, 2)",
+        "10 PRINT MAX(1)",
+        "10 PRINT RND(1, 2)",
+    ];
+
+    for program in params {
+        let Err(err) = Interpreter::from_str(program) else {
+            bail!("Program should not parse: {program}");
+        };
+        let InterpreterError::ParseErrorError(ParseError::WrongNumberOfArguments { .. }) = err
+        else {
+            bail!("Unexpected error for {program}: {err:?}");
+        };
+    }
+
+    assert!(Interpreter::from_str("10 LET ABS = 1").is_err());
+    Ok(())
+}
+
+#[test]
+pub fn test_builtin_errors() -> Result<()> {
+    let err = run_program("10 PRINT SQR(-1)").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::InvalidArgument {
+            function: "SQR",
+            ..
+        })
+    ));
+
+    let err = run_program("10 PRINT MOD(1, 0)").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::DivisionByZero { line_id: 10, .. })
+    ));
+    Ok(())
+}
+
+#[test]
+pub fn test_randomize() -> Result<()> {
+    let program = "10 RANDOMIZE 42\n20 PRINT RND, RND(1), RND(0)";
+    let output = run_program(program)?;
+    assert_eq!(output, run_program(program)?);
+
+    let numbers = output
+        .split_whitespace()
+        .map(|n| n.parse::<f64>())
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    assert_eq!(numbers.len(), 3);
+    assert!(numbers.iter().all(|n| (0.0..1.0).contains(n)));
+    assert_ne!(numbers[0], numbers[1]);
+    assert_eq!(numbers[1], numbers[2]);
+
+    assert_ne!(
+        output,
+        run_program("10 RANDOMIZE 7\n20 PRINT RND, RND(1), RND(0)")?
+    );
+    Ok(())
+}
+
+#[test]
+pub fn test_string_functions() -> Result<()> {
+    let params = [
+        (r#"LEN("Grüße")"#, "5"),
+        (r#"LEFT$("Grüße", 3)"#, "Grü"),
+        (r#"LEFT$("abc", 10)"#, "abc"),
+        (r#"RIGHT$("Grüße", 2)"#, "ße"),
+        (r#"MID$("Grüße", 3, 2)"#, "üß"),
+        (r#"MID$("Grüße", 4)"#, "ße"),
+        (r#"LEN(MID$("abc", 5))"#, "0"),
+        ("CHR$(65) + CHR$(8364)", "A€"),
+        (r#"ASC("€uro")"#, "8364"),
+        ("STR$(2.5) + STR$(-3)", "2.5-3"),
+        (r#"VAL(" 42 apples") + 1"#, "43"),
+        (r#"VAL("1.5e2x")"#, "150"),
+        (r#"VAL("-2.5E+1E3")"#, "-25"),
+        (r#"VAL("7e")"#, "7"),
+        (r#"VAL(".e5")"#, "0"),
+        (r#"VAL("apples")"#, "0"),
+        (r#"INSTR(T$, "ö")"#, "12"),
+        (r#"INSTR(4, A$, "bc")"#, "5"),
+        (r#"INSTR(A$, "x")"#, "0"),
+    ];
+
+    // Only one string literal per line, the others are passed in variables
+    let prelude = "10 LET T$ = \"Grüße aus Köln\"\n20 LET A$ = \"abcabc\"\n";
+    for (expression, expected) in params {
+        let output = run_program(&format!("{prelude}30 PRINT {expression}"))?;
+        assert_eq!(output, format!("{expected}\n"), "{expression}");
+    }
+    Ok(())
+}
+
+#[test]
+pub fn test_string_function_errors() -> Result<()> {
+    let params = [
+        (r#"10 PRINT MID$("abc", 0)"#, "MID$", 21),
+        (r#"10 PRINT LEFT$("abc", -1)"#, "LEFT$", 22),
+        (r#"10 PRINT ASC("")"#, "ASC", 13),
+        ("10 PRINT CHR$(-1)", "CHR$", 14),
+        (r#"10 PRINT INSTR(0, A$, "b")"#, "INSTR", 15),
+    ];
+
+    for (program, expected_function, expected_col) in params {
+        let err = run_program(program).unwrap_err();
+        let Some(InterpreterError::InvalidArgument {
+            function, position, ..
+        }) = err.downcast_ref::<InterpreterError>()
+        else {
+            bail!("Unexpected error for {program}: {err:?}");
+        };
+        assert_eq!(*function, expected_function);
+        assert_eq!(position.col_start, expected_col, "{program}");
+    }
+
+    let err = run_program("10 PRINT LEN(5)").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::TypeMismatch {
+            expected: "string",
+            ..
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+pub fn test_def_fn() -> Result<()> {
+    let program = "\
+10 DEF FNSQUARE(X) = X * X
+20 DEF FNHYP(A, B) = SQR(FNSQUARE(A) + FNSQUARE(B))
+30 DEF FNPI = 3.14159
+40 LET X = 7
+50 PRINT FNSQUARE(3), FNHYP(3, 4), X
+60 DEF fnGreet$(N$) = LEFT$(N$, 1)
+70 PRINT FNGREET$(\"World\")
+80 PRINT INT(FNPI() * 100), FNPI
+90 DEF FNSQUARE(X) = X * X * X
+100 PRINT FNSQUARE(2)
+";
+    let output = run_program(program)?;
+    assert_eq!(output, "9\t5\t7\nW\n314\t3.14159\n8\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_def_fn_errors() -> Result<()> {
+    let params = [
+        ("10 PRINT FNA(1)\n20 DEF FNA(X) = X", "UnknownFunction"),
+        (
+            "10 DEF FNA(X) = X\n20 PRINT FNA(1, 2)",
+            "WrongNumberOfArguments",
+        ),
+        ("10 DEF FNA(X) = FNA(X + 1)\n20 PRINT FNA(1)", "CallTooDeep"),
+        ("10 DEF FNA(X) = X\n20 PRINT FNA(\"A\")", "TypeMismatch"),
+        ("10 DEF FNA$(X) = X\n20 PRINT FNA$(1)", "TypeMismatch"),
+    ];
+
+    for (program, expected) in params {
+        let err = run_program(program).unwrap_err();
+        let matched = match err.downcast_ref::<InterpreterError>() {
+            Some(InterpreterError::UnknownFunction { name, .. }) => {
+                assert_eq!(name, "FNA");
+                "UnknownFunction"
+            }
+            Some(InterpreterError::WrongNumberOfArguments {
+                expected: 1,
+                actual: 2,
+                ..
+            }) => "WrongNumberOfArguments",
+            Some(InterpreterError::CallTooDeep { .. }) => "CallTooDeep",
+            Some(InterpreterError::TypeMismatch { .. }) => "TypeMismatch",
+            _ => bail!("Unexpected error for {program}: {err:?}"),
+        };
+        assert_eq!(matched, expected, "{program}");
+    }
+
+    // The parameters shadow the globals only during the call
+    let output = run_program("10 LET X = 5\n20 DEF FNA(X, Y) = X * Y\n30 PRINT FNA(2, 3), X")?;
+    assert_eq!(output, "6\t5\n");
+    assert!(run_program("10 DEF FNA(Y) = Y\n20 PRINT FNA(1)\n30 PRINT Y").is_err());
+
+    assert!(Interpreter::from_str("10 LET FNA = 1").is_err());
+    Ok(())
+}
+
+#[test]
+pub fn test_data_read_restore() -> Result<()> {
+    let program = r#"
+10 DATA 3, -1.5
+20 READ A, B
+30 IF A > 0 THEN DATA "Hello, World"
+40 READ C$ : PRINT A, B, C$
+50 RESTORE 30 : READ D$ : PRINT D$
+60 RESTORE : READ E : PRINT E
+70 DATA 7
+80 RESTORE 70 : READ F : PRINT F
+"#;
+    let output = run_program(program)?;
+    assert_eq!(output, "3\t-1.5\tHello, World\nHello, World\n3\n7\n");
+    Ok(())
+}
+
+#[test]
+pub fn test_data_errors() -> Result<()> {
+    let err = run_program("10 DATA 1\n20 READ A, B").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::OutOfData { line_id: 20 })
+    ));
+
+    let err = run_program("10 DATA 1\n20 READ A$").unwrap_err();
+    let Some(InterpreterError::TypeMismatch { position, .. }) =
+        err.downcast_ref::<InterpreterError>()
+    else {
+        bail!("Unexpected error: {err:?}");
+    };
+    assert_eq!((position.line_num, position.col_start), (0, 8));
+
+    let err = run_program("10 DATA 1\n20 RESTORE 15").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::InvalidRestore(15))
+    ));
+
+    assert!(Interpreter::from_str("10 DATA A").is_err());
+    Ok(())
+}
+
+#[test]
+pub fn test_on_goto_gosub() -> Result<()> {
+    let program = "\
+10 FOR I = -1 TO 4
+20 ON I GOTO 100, 200, 300
+30 PRINT 0
+40 NEXT I
+50 ON 2.7 GOSUB 400, 500 : PRINT 6
+60 END
+100 PRINT 1 : GOTO 40
+200 PRINT 2 : GOTO 40
+300 PRINT 3 : GOTO 40
+400 PRINT 4 : RETURN
+500 PRINT 5 : RETURN
+";
+    let output = run_program(program)?;
+    assert_eq!(output, "0\n0\n1\n2\n3\n0\n5\n6\n");
+
+    let result = Interpreter::from_str("10 ON 1 GOTO 20, 30\n20 PRINT 1");
+    assert!(matches!(
+        result,
+        Err(InterpreterError::ParseErrorError(
+            ParseError::UndefinedLine { line_id: 30, .. }
+        ))
+    ));
+
+    let err = run_program("10 ON 1 GOSUB 10 * 2").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::InvalidGoto(20))
+    ));
+    Ok(())
+}
+
+#[test]
+pub fn test_on_error_resume() -> Result<()> {
+    let program = "\
+10 ON ERROR GOTO 100
+20 LET D = 0
+30 PRINT 10 / D : PRINT 2
+40 PRINT ERR, ERL
+50 READ A
+60 PRINT A
+70 END
+100 PRINT ERR, ERL
+110 IF ERR = 11 THEN LET D = 5 : RESUME
+120 RESUME NEXT
+";
+    let output = run_program(program)?;
+    assert_eq!(output, "11\t30\n2\n2\n11\t30\n4\t50\n100\t60\n");
+
+    let program = "\
+10 ON ERROR GOTO 100
+20 PRINT ABS(\"A\")
+30 PRINT 1
+40 END
+100 RESUME 40
+";
+    assert_eq!(run_program(program)?, "");
+    Ok(())
+}
+
+#[test]
+pub fn test_error_handling_errors() -> Result<()> {
+    // An error in the handler aborts the program
+    let err = run_program("10 ON ERROR GOTO 100\n20 PRINT 1 / 0\n100 RETURN").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::ReturnWithoutGosub)
+    ));
+
+    // ON ERROR GOTO 0 removes the handler
+    let err =
+        run_program("10 ON ERROR GOTO 100\n20 ON ERROR GOTO 0\n30 PRINT 1 / 0\n100 RESUME NEXT")
+            .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::DivisionByZero { .. })
+    ));
+
+    let err = run_program("10 RESUME").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::ResumeWithoutError)
+    ));
+
+    let result = Interpreter::from_str("10 ON ERROR GOTO 20");
+    assert!(matches!(
+        result,
+        Err(InterpreterError::ParseErrorError(
+            ParseError::UndefinedLine { line_id: 20, .. }
+        ))
+    ));
+
+    assert_eq!(InterpreterError::EndOfInput.code(), Some(62));
+    assert_eq!(InterpreterError::Finished.code(), None);
+    Ok(())
+}
+
+#[test]
+pub fn test_operators() -> Result<()> {
+    let program = "\
+10 PRINT 2 ^ 10, 2 ^ 3 ^ 2, -2 ^ 2, 2 ^ -1, 4 ^ 0.5
+20 PRINT 7 \\ 2, -7 \\ 2, 7.9 \\ 2, 7 MOD 3, -7 MOD 3, 7.5 MOD 2
+30 PRINT 1 + 7 MOD 4 * 2, 12 \\ 2 * 3, MOD(7, 3)
+40 PRINT 6 AND 3, 6 OR 3, 6 XOR 3, -1 AND 255, 5.7 AND 7
+50 LET A = 5
+60 IF (A AND 1) = 1 AND A MOD 2 = 1 THEN PRINT A
+70 PRINT 1 ^ 5000000000, 0 ^ 5000000000, (-1) ^ 5000000000, (-1) ^ 5000000001
+";
+    let output = run_program(program)?;
+    assert_eq!(
+        output,
+        "1024\t512\t-4\t0.5\t2\n3\t-3\t3\t1\t-1\t1.5\n8\t2\t1\n2\t7\t5\t255\t5\n5\n1\t0\t1\t-1\n"
+    );
+    Ok(())
+}
+
+#[test]
+pub fn test_operator_errors() -> Result<()> {
+    for program in [
+        "10 PRINT 2 ^ 64",
+        "10 PRINT 2 ^ 5000000000",
+        "10 PRINT 10.0 ^ 400",
+        "10 LET A = 1 - 9223372036854775807 - 2\n20 PRINT A \\ -1",
+        "10 PRINT 1E30 AND 1",
+    ] {
+        let err = run_program(program).unwrap_err();
+        assert!(
+            matches!(
+                err.downcast_ref::<InterpreterError>(),
+                Some(InterpreterError::Overflow { .. })
+            ),
+            "{program}: {err}"
+        );
+    }
+
+    for program in ["10 PRINT 1 \\ 0.5", "10 PRINT 1 MOD 0", "10 PRINT 0 ^ -1"] {
+        let err = run_program(program).unwrap_err();
+        assert!(
+            matches!(
+                err.downcast_ref::<InterpreterError>(),
+                Some(InterpreterError::DivisionByZero { .. })
+            ),
+            "{program}: {err}"
+        );
+    }
+
+    let err = run_program("10 PRINT (-8) ^ 0.5").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::InvalidArgument { function: "^", .. })
+    ));
+
+    let err = run_program("10 LET A$ = \"A\"\n20 PRINT A$ AND 1").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<InterpreterError>(),
+        Some(InterpreterError::TypeMismatch { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+pub fn test_keyword_modes() -> Result<()> {
+    let program = "\
+10 LET PRINTER = 2
+20 LET REMAINDER = 7 MOD PRINTER
+30 PRINT PRINTER, REMAINDER REM both are variables
+";
+    assert_eq!(run_program(program)?, "2\t1\n");
+
+    let program = "10 FORI=1TO3:PRINTI:NEXTI";
+    let mut output = Vec::<u8>::new();
+    let options = ParseOptions {
+        keyword_mode: KeywordMode::Crunched,
+        ..ParseOptions::default()
+    };
+    let mut interpreter = Interpreter::from_str_with(program, &options)?;
+    interpreter.run(&mut "".as_bytes(), &mut output)?;
+    assert_eq!(String::from_utf8(output)?, "1\n2\n3\n");
+
+    // Without crunching FORI is a single name
+    assert!(run_program(program).is_err());
+    Ok(())
+}
+
+#[test]
+pub fn test_number_literals() -> Result<()> {
+    let program = "\
+10 LET A = 5
+20 PRINT A-1, A - -1, -9223372036854775807-1
+30 DATA -9223372036854775807
+40 READ B
+50 PRINT B
+";
+    assert_eq!(
+        run_program(program)?,
+        "4\t6\t-9223372036854775808\n-9223372036854775807\n"
+    );
+
+    for program in ["10 PRINT 9223372036854775808", "10 DATA 1E400"] {
+        let result = Interpreter::from_str(program);
+        assert!(
+            matches!(
+                result,
+                Err(InterpreterError::ParseErrorError(
+                    ParseError::NumberTooLarge { .. }
+                ))
+            ),
+            "{program}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+pub fn test_string_literals() -> Result<()> {
+    let program = r#"
+10 LET X = 1
+20 PRINT "A", X, "B"
+30 PRINT "She said ""Hello""" + "!"
+40 PRINT LEN("""")
+"#;
+    assert_eq!(run_program(program)?, "A\t1\tB\nShe said \"Hello\"!\n1\n");
+
+    let result = Interpreter::from_str("10 PRINT \"A\"\n20 PRINT \"B, 1");
+    let Err(InterpreterError::ParseErrorError(ParseError::UnterminatedString { position })) =
+        result
+    else {
+        panic!("Expected an unterminated string");
+    };
+    assert_eq!((position.line_num, position.col_start), (1, 9));
+    Ok(())
+}
+
+#[test]
+pub fn test_line_order_option() -> Result<()> {
+    let program = "20 PRINT 2\n10 PRINT 1";
+    assert_eq!(run_program(program)?, "1\n2\n");
+
+    let options = ParseOptions {
+        line_order: LineOrder::Reject,
+        ..ParseOptions::default()
+    };
+    let result = Interpreter::from_str_with(program, &options);
+    assert!(matches!(
+        result,
+        Err(InterpreterError::ParseErrorError(
+            ParseError::UnsortedLineNumber { line_id: 10, .. }
+        ))
+    ));
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use glob::glob;
+use nanobasic::parser::tokenizer::{KeywordMode, tokenize_with, tokenize_with_regex};
+
+const TEST_DIR: &str = "Examples";
+
+#[test]
+fn test_scanner_matches_regex_tokenizer() {
+    let mut p = TEST_DIR.to_string();
+    p.push_str("/*.bas");
+
+    let pattern = glob(&p).expect("invalid pattern");
+
+    for path_result in pattern {
+        let path = path_result.unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            tokenize_with(&lines, KeywordMode::Crunched).unwrap(),
+            tokenize_with_regex(&lines).unwrap(),
+            "{path:?}"
+        );
+    }
+}
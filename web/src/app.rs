@@ -1,9 +1,24 @@
 use anyhow::Result;
 use leptos::prelude::*;
-use nanobasic::interpreter::Interpreter;
+use nanobasic::interpreter::{Interpreter, ProgramState};
+use std::io::Write;
 use std::rc::Rc;
 
 const PROGRAMS: &[(&str, &str)] = &[
+    (
+        "Arrays",
+        include_str!(r"../../nanobasic/Examples/arrays.bas"),
+    ),
+    (
+        "Cipher",
+        include_str!(r"../../nanobasic/Examples/cipher.bas"),
+    ),
+    ("Data", include_str!(r"../../nanobasic/Examples/data.bas")),
+    ("Else", include_str!(r"../../nanobasic/Examples/else.bas")),
+    (
+        "Errors",
+        include_str!(r"../../nanobasic/Examples/errors.bas"),
+    ),
     (
         "Factorial",
         include_str!(r"../../nanobasic/Examples/factorial.bas"),
@@ -12,11 +27,21 @@ const PROGRAMS: &[(&str, &str)] = &[
         "Fibonacci",
         include_str!(r"../../nanobasic/Examples/fib.bas"),
     ),
+    (
+        "Floats",
+        include_str!(r"../../nanobasic/Examples/floats.bas"),
+    ),
+    ("For", include_str!(r"../../nanobasic/Examples/for.bas")),
+    (
+        "Functions",
+        include_str!(r"../../nanobasic/Examples/functions.bas"),
+    ),
     ("GCD", include_str!(r"../../nanobasic/Examples/gcd.bas")),
     ("Gosub", include_str!(r"../../nanobasic/Examples/gosub.bas")),
     ("Goto", include_str!(r"../../nanobasic/Examples/goto.bas")),
     ("If1", include_str!(r"../../nanobasic/Examples/if1.bas")),
     ("If2", include_str!(r"../../nanobasic/Examples/if2.bas")),
+    ("On", include_str!(r"../../nanobasic/Examples/on.bas")),
     (
         "Print1",
         include_str!(r"../../nanobasic/Examples/print1.bas"),
@@ -29,23 +54,32 @@ const PROGRAMS: &[(&str, &str)] = &[
         "Print3",
         include_str!(r"../../nanobasic/Examples/print3.bas"),
     ),
+    (
+        "Strings",
+        include_str!(r"../../nanobasic/Examples/strings.bas"),
+    ),
     (
         "Variables",
         include_str!(r"../../nanobasic/Examples/variables.bas"),
     ),
+    ("While", include_str!(r"../../nanobasic/Examples/while.bas")),
 ];
 
 const MAX_EXE_LINES: usize = 10000;
 
-fn run_nano(source: &str) -> Result<(String, String)> {
+fn run_nano(source: &str, input: &str) -> Result<(String, String)> {
     let mut stream = Vec::<u8>::new();
+    let mut input = input.as_bytes();
     let mut interpreter = Interpreter::from_str(source)?;
     let mut count_lines = 0usize;
 
     while !interpreter.finished() & (count_lines < MAX_EXE_LINES) {
-        interpreter.step_line(&mut stream)?;
+        interpreter.step_line(&mut input, &mut stream)?;
         count_lines += 1;
     }
+    if let ProgramState::Stopped { line_id } = interpreter.state() {
+        writeln!(stream, "BREAK IN {line_id}")?;
+    }
     let ast = interpreter.ast_json_pretty()?;
     let result = String::from_utf8(stream)?;
     Ok((result, ast))
@@ -81,7 +115,7 @@ fn Header() -> impl IntoView {
 
 #[component]
 fn SelectProgram(set_active_program: WriteSignal<String>) -> impl IntoView {
-    let programs: Rc<Vec<(&str, &str)>> = Rc::new(PROGRAMS.iter().copied().collect());
+    let programs: Rc<Vec<(&str, &str)>> = Rc::new(PROGRAMS.to_vec());
 
     let (selected_idx, set_selected_idx) = signal(0usize);
 
@@ -118,6 +152,7 @@ fn SelectProgram(set_active_program: WriteSignal<String>) -> impl IntoView {
 #[component]
 fn ButtonRun(
     active_program: ReadSignal<String>,
+    program_input: ReadSignal<String>,
     set_output: WriteSignal<String>,
     set_ast: WriteSignal<String>,
 ) -> impl IntoView {
@@ -127,7 +162,8 @@ fn ButtonRun(
                 class="bg-blue-500 hover:bg-blue-600 text-white font-semibold p-2 rounded shadow w-full"
                 on:click=move |_| {
                     let code = active_program.get();
-                    match run_nano(&code) {
+                    let input = program_input.get();
+                    match run_nano(&code, &input) {
                         Ok((output, ast)) => {
                             set_output.set(output);
                             set_ast.set(ast);
@@ -163,6 +199,27 @@ pub fn ProgramSource(
     }
 }
 
+#[component]
+pub fn ProgramInput(
+    program_input: ReadSignal<String>,
+    set_program_input: WriteSignal<String>,
+) -> impl IntoView {
+    view! {
+        <div class="flex flex-col w-full">
+            <h2 class="text-lg font-semibold mb-1">"Program input (one line per INPUT)"</h2>
+            <textarea
+                class="w-full min-h-[60px] resize-y border border-blue-300 rounded p-3 text-sm sm:text-base focus:ring-2 focus:ring-blue-400 outline-none"
+                autocapitalize="off"
+                prop:value=program_input
+                on:input=move |ev| {
+                    let val = event_target_value(&ev);
+                    set_program_input.set(val);
+                }
+            />
+        </div>
+    }
+}
+
 #[component]
 pub fn DisplayAST(ast: ReadSignal<String>) -> impl IntoView {
     view! {
@@ -230,13 +287,14 @@ pub fn App() -> impl IntoView {
     // state: output of program
     let (output, set_output) = signal(String::new());
     let (ast, set_ast) = signal(String::new());
+    let (program_input, set_program_input) = signal(String::new());
 
     // list of demo programs
-    let programs: Rc<Vec<(&str, &str)>> = Rc::new(PROGRAMS.iter().copied().collect());
+    let programs: Rc<Vec<(&str, &str)>> = Rc::new(PROGRAMS.to_vec());
     let (active_program, set_active_program) = signal(String::new());
 
     // pre-populate the active_program with the first entry
-    if let Some((_, src)) = programs.get(0) {
+    if let Some((_, src)) = programs.first() {
         set_active_program.set(src.to_string());
     }
 
@@ -245,7 +303,8 @@ pub fn App() -> impl IntoView {
             <Header />
             <SelectProgram set_active_program />
             <ProgramSource active_program set_active_program />
-            <ButtonRun active_program set_output set_ast />
+            <ProgramInput program_input set_program_input />
+            <ButtonRun active_program program_input set_output set_ast />
 
             <ProgramOutput output />
             <DisplayAST ast />